@@ -0,0 +1,438 @@
+//! Graph algorithms. Every function here is generic over the traits in `prelude`,
+//! so it works on any graph representation implementing them, views included.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use crate::prelude::{IntoEdges, IntoNeighbors, NodeCount, NodeId, VisitMap, Visitable};
+
+/// A breadth first iterator over the nodes reachable from a starting node
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, algo::Bfs};
+/// let mut graph = Graph::new();
+/// let a = graph.add_node(());
+/// let b = graph.add_node(());
+/// let c = graph.add_node(());
+/// graph.add_edge(a, b, 1);
+/// graph.add_edge(b, c, 1);
+/// assert_eq!(Bfs::new(&graph, a).collect::<Vec<_>>(), vec![a, b, c]);
+/// ```
+pub struct Bfs<'a, G: Visitable> {
+    graph: &'a G,
+    queue: VecDeque<NodeId>,
+    visited: G::Map,
+}
+
+impl<'a, G: IntoNeighbors + Visitable> Bfs<'a, G> {
+    pub fn new(graph: &'a G, start: NodeId) -> Self {
+        let mut visited = graph.visit_map();
+        visited.visit(start);
+        Self {
+            graph,
+            queue: vec![start].into(),
+            visited,
+        }
+    }
+}
+
+impl<'a, G: IntoNeighbors + Visitable> Iterator for Bfs<'a, G> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        for next in self.graph.neighbors(node) {
+            if self.visited.visit(next) {
+                self.queue.push_back(next);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+/// A depth first, pre-order iterator over the nodes reachable from a starting node
+pub struct Dfs<'a, G: Visitable> {
+    graph: &'a G,
+    stack: Vec<NodeId>,
+    visited: G::Map,
+}
+
+impl<'a, G: IntoNeighbors + Visitable> Dfs<'a, G> {
+    pub fn new(graph: &'a G, start: NodeId) -> Self {
+        Self {
+            graph,
+            stack: vec![start],
+            visited: graph.visit_map(),
+        }
+    }
+}
+
+impl<'a, G: IntoNeighbors + Visitable> Iterator for Dfs<'a, G> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if self.visited.visit(node) {
+                // Push in reverse so that neighbors are visited in their natural order
+                let visited = &self.visited;
+                let neighbors: Vec<NodeId> = self
+                    .graph
+                    .neighbors(node)
+                    .filter(|n| !visited.is_visited(*n))
+                    .collect();
+                self.stack.extend(neighbors.into_iter().rev());
+                return Some(node);
+            }
+        }
+
+        None
+    }
+}
+
+/// Computes the length of the shortest path from `start` to every other node,
+/// using edge weights as costs. Unreachable nodes are reported as `None`.
+pub fn dijkstra<G: IntoEdges + NodeCount>(graph: &G, start: NodeId) -> Vec<Option<usize>> {
+    let mut distances = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    distances[start] = Some(0);
+    heap.push(Reverse((0, start)));
+
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if distances[node].is_some_and(|d| cost > d) {
+            continue;
+        }
+
+        for edge in graph.edges(node) {
+            let next_cost = cost + edge.weight;
+            if distances[edge.target].is_none_or(|d| next_cost < d) {
+                distances[edge.target] = Some(next_cost);
+                heap.push(Reverse((next_cost, edge.target)));
+            }
+        }
+    }
+
+    distances
+}
+
+/// Finds up to `k` distinct loopless paths from `source` to `target` with Yen's
+/// algorithm, using edge weights as costs. Paths are returned as node lists with
+/// their cost, cheapest first. Parallel edges count as a single step, taken at
+/// the weight of the lightest one.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, algo::k_shortest_paths};
+/// let mut graph = Graph::new();
+/// for _ in 0..4 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 1);
+/// graph.add_edge(1, 3, 1);
+/// graph.add_edge(0, 2, 2);
+/// graph.add_edge(2, 3, 2);
+/// graph.add_edge(0, 3, 5);
+///
+/// let paths = k_shortest_paths(&graph, 0, 3, 5);
+/// assert_eq!(paths, vec![(2, vec![0, 1, 3]), (4, vec![0, 2, 3]), (5, vec![0, 3])]);
+/// ```
+pub fn k_shortest_paths<G: IntoEdges + NodeCount>(
+    graph: &G,
+    source: NodeId,
+    target: NodeId,
+    k: usize,
+) -> Vec<(usize, Vec<NodeId>)> {
+    let n = graph.node_count();
+    let mut paths: Vec<(usize, Vec<NodeId>)> = vec![];
+    let first = match shortest_path(graph, source, target, &vec![false; n], &[]) {
+        Some(path) if k > 0 => path,
+        _ => return paths,
+    };
+
+    let mut candidates = BinaryHeap::new();
+    candidates.push(Reverse(first));
+    while paths.len() < k {
+        let (cost, path) = match candidates.pop() {
+            Some(Reverse(candidate)) => candidate,
+            None => break,
+        };
+        if paths.iter().any(|(_, p)| *p == path) {
+            continue;
+        }
+
+        // Every new candidate leaves the path at some spur node, after sharing a
+        // root with it, and avoids the steps earlier paths took from that root
+        let mut blocked = vec![false; n];
+        let mut root_cost = 0;
+        for i in 0..path.len() - 1 {
+            let spur = path[i];
+            let root = &path[..=i];
+            let avoided: Vec<NodeId> = paths
+                .iter()
+                .map(|(_, p)| p)
+                .chain(std::iter::once(&path))
+                .filter(|p| p.len() > i + 1 && p[..=i] == *root)
+                .map(|p| p[i + 1])
+                .collect();
+
+            if let Some((spur_cost, spur_path)) =
+                shortest_path(graph, spur, target, &blocked, &avoided)
+            {
+                let mut full = root[..i].to_vec();
+                full.extend(spur_path);
+                candidates.push(Reverse((root_cost + spur_cost, full)));
+            }
+
+            blocked[spur] = true;
+            root_cost += step_cost(graph, spur, path[i + 1]);
+        }
+        paths.push((cost, path));
+    }
+
+    paths
+}
+
+/// The weight of the lightest edge going from `from` to `to`
+fn step_cost<G: IntoEdges>(graph: &G, from: NodeId, to: NodeId) -> usize {
+    graph
+        .edges(from)
+        .filter(|e| e.target == to)
+        .map(|e| e.weight)
+        .min()
+        .unwrap()
+}
+
+/// Dijkstra's algorithm stopping at `target`, never entering `blocked` nodes nor
+/// taking a first step into `avoided` ones
+fn shortest_path<G: IntoEdges + NodeCount>(
+    graph: &G,
+    source: NodeId,
+    target: NodeId,
+    blocked: &[bool],
+    avoided: &[NodeId],
+) -> Option<(usize, Vec<NodeId>)> {
+    let mut distances = vec![None; graph.node_count()];
+    let mut previous = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    distances[source] = Some(0);
+    heap.push(Reverse((0, source)));
+
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if distances[node].is_some_and(|d| cost > d) {
+            continue;
+        }
+        if node == target {
+            let mut path = vec![target];
+            let mut current = target;
+            while let Some(p) = previous[current] {
+                path.push(p);
+                current = p;
+            }
+            path.reverse();
+            return Some((cost, path));
+        }
+
+        for edge in graph.edges(node) {
+            if blocked[edge.target] || (node == source && avoided.contains(&edge.target)) {
+                continue;
+            }
+            let next_cost = cost + edge.weight;
+            if edge.target != source && distances[edge.target].is_none_or(|d| next_cost < d) {
+                distances[edge.target] = Some(next_cost);
+                previous[edge.target] = Some(node);
+                heap.push(Reverse((next_cost, edge.target)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Returned by algorithms that require an acyclic graph, holding a node that
+/// belongs to a cycle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cycle(NodeId);
+
+impl Cycle {
+    pub fn node(&self) -> NodeId {
+        self.0
+    }
+}
+
+/// Returned by exact algorithms that refuse to run on graphs too large to finish
+/// in a reasonable time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TooManyNodes {
+    pub nodes: usize,
+    pub limit: usize,
+}
+
+/// Sorts the nodes of a directed graph so that every edge goes from an earlier
+/// node to a later one. Fails if the graph has a cycle. Hidden nodes are left out.
+pub fn toposort<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Vec<NodeId>, Cycle> {
+    let n = graph.node_count();
+    let mut in_degree = vec![0usize; n];
+    for node in 0..n {
+        for next in graph.neighbors(node) {
+            in_degree[next] += 1;
+        }
+    }
+
+    let mut queue: VecDeque<NodeId> = graph
+        .node_ids()
+        .filter(|node| in_degree[*node] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for next in graph.neighbors(node) {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    match (0..n).find(|node| in_degree[*node] > 0) {
+        Some(node) => Err(Cycle(node)),
+        None => Ok(order),
+    }
+}
+
+/// Labels every node with the index of its connected component. Components are
+/// numbered in order of their smallest node. Edge direction is ignored, so on
+/// directed graphs this finds the weakly connected components. Hidden nodes
+/// belong to no component and are labelled `usize::MAX`.
+pub fn connected_components<G: IntoNeighbors + NodeCount>(graph: &G) -> Vec<usize> {
+    fn find(parents: &mut [NodeId], mut node: NodeId) -> NodeId {
+        while parents[node] != node {
+            parents[node] = parents[parents[node]];
+            node = parents[node];
+        }
+
+        node
+    }
+
+    let n = graph.node_count();
+    let mut parents: Vec<NodeId> = (0..n).collect();
+    for node in 0..n {
+        for next in graph.neighbors(node) {
+            let (a, b) = (find(&mut parents, node), find(&mut parents, next));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut labels = vec![usize::MAX; n];
+    let mut count = 0;
+    for node in graph.node_ids() {
+        let root = find(&mut parents, node);
+        if labels[root] == usize::MAX {
+            labels[root] = count;
+            count += 1;
+        }
+        labels[node] = labels[root];
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Graph, MatrixGraph};
+
+    fn make_test_graph() -> Graph {
+        let mut graph = Graph::new();
+        for _ in 0..5 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 4);
+        graph.add_edge(0, 2, 1);
+        graph.add_edge(2, 1, 2);
+        graph.add_edge(1, 3, 5);
+
+        graph
+    }
+
+    #[test]
+    fn bfs() {
+        let graph = make_test_graph();
+        assert_eq!(Bfs::new(&graph, 0).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn dfs() {
+        let graph = make_test_graph();
+        assert_eq!(Dfs::new(&graph, 0).collect::<Vec<_>>(), vec![0, 1, 3, 2]);
+    }
+
+    #[test]
+    fn dijkstra_on_every_representation() {
+        let expected = vec![Some(0), Some(3), Some(1), Some(8), None];
+        assert_eq!(dijkstra(&make_test_graph(), 0), expected);
+        assert_eq!(dijkstra(&MatrixGraph::from(make_test_graph()), 0), expected);
+    }
+
+    #[test]
+    fn yen_k_shortest_paths() {
+        // The example from Yen's original paper, with nodes C D E F G H as 0..6
+        let mut graph = Graph::new();
+        for _ in 0..6 {
+            graph.add_node(());
+        }
+        for (a, b, w) in [
+            (0, 1, 3),
+            (0, 2, 2),
+            (1, 3, 4),
+            (2, 1, 1),
+            (2, 3, 2),
+            (2, 4, 3),
+            (3, 4, 2),
+            (3, 5, 1),
+            (4, 5, 2),
+        ]
+        .iter()
+        {
+            graph.add_edge(*a, *b, *w);
+        }
+
+        let paths = k_shortest_paths(&graph, 0, 5, 3);
+        assert_eq!(
+            paths,
+            vec![
+                (5, vec![0, 2, 3, 5]),
+                (7, vec![0, 2, 4, 5]),
+                (8, vec![0, 1, 3, 5])
+            ]
+        );
+        assert_eq!(k_shortest_paths(&graph, 0, 5, 100).len(), 7);
+        assert!(k_shortest_paths(&graph, 5, 0, 3).is_empty());
+        assert_eq!(k_shortest_paths(&graph, 0, 0, 3), vec![(0, vec![0])]);
+    }
+
+    #[test]
+    fn k_shortest_paths_are_loopless_when_undirected() {
+        let graph = crate::generators::cycle(4);
+        let paths = k_shortest_paths(&graph, 0, 2, 5);
+        assert_eq!(paths, vec![(2, vec![0, 1, 2]), (2, vec![0, 3, 2])]);
+    }
+
+    #[test]
+    fn toposort_detects_cycles() {
+        let mut graph = make_test_graph();
+        assert_eq!(toposort(&graph), Ok(vec![0, 4, 2, 1, 3]));
+        graph.add_edge(3, 0, 1);
+        assert!(toposort(&graph).is_err());
+    }
+
+    #[test]
+    fn components() {
+        let mut graph = make_test_graph();
+        graph.add_node(());
+        graph.add_edge(5, 4, 1);
+        assert_eq!(connected_components(&graph), vec![0, 0, 0, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,362 @@
+//! Node centrality measures. Every function returns a vector of scores indexed
+//! by node id. Hidden nodes score `0` and do not count towards normalization.
+//!
+//! Parallel edges count once each: they add to the degree of their endpoints,
+//! are proportionally more likely to be followed by random walks, and give
+//! distinct shortest paths when they are among the lightest.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    algo::dijkstra,
+    prelude::{IntoEdges, IntoNeighbors, NodeCount, NodeId},
+};
+
+/// Returned by iterative measures that did not converge within the allowed number
+/// of iterations, holding the last estimate
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotConverged(pub Vec<f64>);
+
+/// The fraction of the other nodes each node is connected to. On directed graphs
+/// both incoming and outgoing edges count.
+pub fn degree_centrality<G: IntoNeighbors + NodeCount>(graph: &G) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut degrees = vec![0usize; n];
+    for node in 0..n {
+        for next in graph.neighbors(node) {
+            degrees[node] += 1;
+            if graph.is_directed() {
+                degrees[next] += 1;
+            }
+        }
+    }
+
+    let count = graph.node_ids().count();
+    let scale = if count > 1 {
+        1.0 / (count - 1) as f64
+    } else {
+        1.0
+    };
+    degrees.into_iter().map(|d| d as f64 * scale).collect()
+}
+
+/// Ranks nodes by the stationary distribution of a random walk that follows an
+/// edge with probability `damping` and jumps to a random node otherwise. Nodes
+/// without outgoing edges spread their rank evenly.
+///
+/// Iterates until the total change of the scores drops below `tolerance`.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{centrality::pagerank, generators};
+/// let ranks = pagerank(&generators::star(4), 0.85, 1e-6, 100).unwrap();
+/// assert!(ranks[0] > ranks[1]);
+/// assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+/// ```
+pub fn pagerank<G: IntoNeighbors + NodeCount>(
+    graph: &G,
+    damping: f64,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<Vec<f64>, NotConverged> {
+    let n = graph.node_count();
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    if nodes.is_empty() {
+        return Ok(vec![0.0; n]);
+    }
+
+    let count = nodes.len() as f64;
+    let out_degrees: Vec<usize> = (0..n).map(|node| graph.neighbors(node).count()).collect();
+    let mut ranks = vec![0.0; n];
+    for &node in &nodes {
+        ranks[node] = 1.0 / count;
+    }
+    for _ in 0..max_iterations {
+        let dangling: f64 = nodes
+            .iter()
+            .filter(|node| out_degrees[**node] == 0)
+            .map(|node| ranks[*node])
+            .sum();
+        let base = (1.0 - damping) / count + damping * dangling / count;
+        let mut next = vec![0.0; n];
+        for &node in &nodes {
+            next[node] = base;
+        }
+        for node in 0..n {
+            if out_degrees[node] > 0 {
+                let share = damping * ranks[node] / out_degrees[node] as f64;
+                for target in graph.neighbors(node) {
+                    next[target] += share;
+                }
+            }
+        }
+
+        let change: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if change < tolerance {
+            return Ok(ranks);
+        }
+    }
+
+    Err(NotConverged(ranks))
+}
+
+/// Scores each node by the principal eigenvector of the adjacency matrix: a node
+/// is important when the nodes pointing to it are. Scores have unit euclidean norm.
+///
+/// Iterates until the total change of the scores drops below `tolerance`.
+pub fn eigenvector_centrality<G: IntoNeighbors + NodeCount>(
+    graph: &G,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<Vec<f64>, NotConverged> {
+    let n = graph.node_count();
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    if nodes.is_empty() {
+        return Ok(vec![0.0; n]);
+    }
+
+    let mut scores = vec![0.0; n];
+    for &node in &nodes {
+        scores[node] = 1.0 / nodes.len() as f64;
+    }
+    for _ in 0..max_iterations {
+        // Iterating on A + I instead of A keeps bipartite graphs from oscillating
+        let mut next = scores.clone();
+        for (node, score) in scores.iter().enumerate() {
+            for target in graph.neighbors(node) {
+                next[target] += score;
+            }
+        }
+
+        let norm = next.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return Ok(next);
+        }
+        next.iter_mut().for_each(|x| *x /= norm);
+
+        let change: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+        scores = next;
+        if change < nodes.len() as f64 * tolerance {
+            return Ok(scores);
+        }
+    }
+
+    Err(NotConverged(scores))
+}
+
+/// The inverse of the average distance from each node to the nodes it can reach,
+/// scaled by the fraction of the graph it reaches (Wasserman and Faust). Distances
+/// are measured along outgoing edges, using edge weights.
+pub fn closeness_centrality<G: IntoEdges + NodeCount>(graph: &G) -> Vec<f64> {
+    let count = graph.node_ids().count();
+    (0..graph.node_count())
+        .map(|node| {
+            if !graph.contains_node(node) {
+                return 0.0;
+            }
+
+            let distances = dijkstra(graph, node);
+            let reached = distances.iter().flatten().count() - 1;
+            let total: usize = distances.iter().flatten().sum();
+            if total == 0 {
+                0.0
+            } else {
+                let closeness = reached as f64 / total as f64;
+                closeness * reached as f64 / (count - 1) as f64
+            }
+        })
+        .collect()
+}
+
+/// The fraction of shortest paths between pairs of other nodes that pass through
+/// each node, computed with Brandes' algorithm using edge weights as lengths.
+/// When `normalized` is set, scores are divided by the number of such pairs.
+pub fn betweenness_centrality<G: IntoEdges + NodeCount>(graph: &G, normalized: bool) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut centrality = vec![0.0; n];
+
+    for source in graph.node_ids() {
+        let (order, predecessors, paths) = shortest_path_dag(graph, source);
+
+        let mut dependency = vec![0.0; n];
+        for &node in order.iter().rev() {
+            for &pred in &predecessors[node] {
+                dependency[pred] += paths[pred] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                centrality[node] += dependency[node];
+            }
+        }
+    }
+
+    // Undirected paths were counted once from each end, which normalizing by
+    // the number of ordered pairs already accounts for
+    let count = graph.node_ids().count();
+    let scale = if normalized && count > 2 {
+        1.0 / ((count - 1) * (count - 2)) as f64
+    } else if graph.is_directed() {
+        1.0
+    } else {
+        0.5
+    };
+
+    centrality.into_iter().map(|c| c * scale).collect()
+}
+
+/// Runs Dijkstra from `source`, returning the nodes in order of distance, the
+/// predecessors of each node on its shortest paths and the number of such paths
+fn shortest_path_dag<G: IntoEdges + NodeCount>(
+    graph: &G,
+    source: NodeId,
+) -> (Vec<NodeId>, Vec<Vec<NodeId>>, Vec<f64>) {
+    let n = graph.node_count();
+    let mut order = vec![];
+    let mut predecessors = vec![vec![]; n];
+    let mut paths = vec![0.0; n];
+    let mut distances: Vec<Option<usize>> = vec![None; n];
+    let mut done = vec![false; n];
+    let mut heap = BinaryHeap::new();
+
+    paths[source] = 1.0;
+    distances[source] = Some(0);
+    heap.push(Reverse((0, source)));
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+        order.push(node);
+
+        for edge in graph.edges(node).filter(|e| !done[e.target]) {
+            let next_cost = cost + edge.weight;
+            match distances[edge.target] {
+                Some(d) if next_cost > d => (),
+                Some(d) if next_cost == d => {
+                    paths[edge.target] += paths[node];
+                    predecessors[edge.target].push(node);
+                }
+                _ => {
+                    distances[edge.target] = Some(next_cost);
+                    paths[edge.target] = paths[node];
+                    predecessors[edge.target] = vec![node];
+                    heap.push(Reverse((next_cost, edge.target)));
+                }
+            }
+        }
+    }
+
+    (order, predecessors, paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn degree() {
+        assert_close(
+            &degree_centrality(&generators::star(4)),
+            &[1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+        );
+    }
+
+    #[test]
+    fn pagerank_with_dangling_node() {
+        let mut graph = Graph::new();
+        for _ in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 0, 1);
+        let ranks = pagerank(&graph, 0.85, 1e-10, 100).unwrap();
+        assert_close(&ranks, &[1.0 / 3.0; 3]);
+
+        graph.add_node(());
+        graph.add_edge(2, 3, 1);
+        let ranks = pagerank(&graph, 0.85, 1e-10, 200).unwrap();
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(pagerank(&graph, 0.85, 1e-10, 2).is_err());
+    }
+
+    #[test]
+    fn eigenvector() {
+        let scores = eigenvector_centrality(&generators::cycle(4), 1e-9, 100).unwrap();
+        assert_close(&scores, &[0.5; 4]);
+        let scores = eigenvector_centrality(&generators::star(3), 1e-9, 200).unwrap();
+        assert_close(&scores, &[0.5f64.sqrt(), 0.5, 0.5]);
+    }
+
+    #[test]
+    fn closeness() {
+        assert_close(
+            &closeness_centrality(&generators::path(3)),
+            &[2.0 / 3.0, 1.0, 2.0 / 3.0],
+        );
+    }
+
+    #[test]
+    fn betweenness() {
+        assert_close(
+            &betweenness_centrality(&generators::star(4), false),
+            &[3.0, 0.0, 0.0, 0.0],
+        );
+        assert_close(
+            &betweenness_centrality(&generators::star(4), true),
+            &[1.0, 0.0, 0.0, 0.0],
+        );
+        // Two equally short paths share the credit
+        assert_close(
+            &betweenness_centrality(&generators::cycle(4), false),
+            &[0.5; 4],
+        );
+    }
+
+    #[test]
+    fn hidden_nodes() {
+        let graph = generators::complete(4);
+        let view = graph.filter_nodes(|node| node.id() != 3);
+        let triangle = generators::complete(3);
+        let padded = |mut scores: Vec<f64>| {
+            scores.push(0.0);
+            scores
+        };
+
+        assert_close(
+            &degree_centrality(&view),
+            &padded(degree_centrality(&triangle)),
+        );
+        assert_close(
+            &pagerank(&view, 0.85, 1e-10, 100).unwrap(),
+            &padded(pagerank(&triangle, 0.85, 1e-10, 100).unwrap()),
+        );
+        assert_close(
+            &eigenvector_centrality(&view, 1e-10, 100).unwrap(),
+            &padded(eigenvector_centrality(&triangle, 1e-10, 100).unwrap()),
+        );
+        assert_close(
+            &closeness_centrality(&view),
+            &padded(closeness_centrality(&triangle)),
+        );
+        assert_close(
+            &betweenness_centrality(&view, true),
+            &padded(betweenness_centrality(&triangle, true)),
+        );
+
+        let path = generators::path(3);
+        let split = path.filter_nodes(|node| node.id() != 1);
+        assert_close(
+            &pagerank(&split, 0.85, 1e-10, 100).unwrap(),
+            &[0.5, 0.0, 0.5],
+        );
+    }
+}
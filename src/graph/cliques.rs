@@ -0,0 +1,326 @@
+//! Cliques and independent sets of undirected graphs. Self loops and hidden
+//! nodes are ignored.
+//!
+//! The exact searches take exponential time, so they refuse graphs with more
+//! than `EXACT_LIMIT` nodes.
+
+use std::collections::HashSet;
+
+use crate::{
+    algo::TooManyNodes,
+    prelude::{IntoNeighbors, NodeCount, NodeId},
+};
+
+/// The largest graph accepted by the exact searches
+pub const EXACT_LIMIT: usize = 64;
+
+fn adjacency<G: IntoNeighbors + NodeCount>(graph: &G) -> Vec<HashSet<NodeId>> {
+    assert!(!graph.is_directed(), "Cliques need an undirected graph");
+    (0..graph.node_count())
+        .map(|node| graph.neighbors(node).filter(|n| *n != node).collect())
+        .collect()
+}
+
+/// One bitmask of neighbors for each node, and the mask of the nodes that are
+/// not hidden
+fn bit_adjacency<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<(Vec<u64>, u64), TooManyNodes> {
+    let n = graph.node_count();
+    if n > EXACT_LIMIT {
+        return Err(TooManyNodes {
+            nodes: n,
+            limit: EXACT_LIMIT,
+        });
+    }
+
+    let adjacency = adjacency(graph)
+        .into_iter()
+        .map(|neighbors| neighbors.into_iter().fold(0, |mask, n| mask | 1 << n))
+        .collect();
+    let nodes = graph.node_ids().fold(0, |mask, n| mask | 1 << n);
+    Ok((adjacency, nodes))
+}
+
+fn nodes_of(mut mask: u64) -> Vec<NodeId> {
+    let mut nodes = vec![];
+    while mask != 0 {
+        nodes.push(mask.trailing_zeros() as NodeId);
+        mask &= mask - 1;
+    }
+
+    nodes
+}
+
+struct Frame {
+    clique: Vec<NodeId>,
+    candidates: Vec<NodeId>,
+    excluded: Vec<NodeId>,
+    branches: Vec<NodeId>,
+}
+
+/// An iterator over the maximal cliques of a graph, found by the Bron–Kerbosch
+/// algorithm with pivoting
+pub struct MaximalCliques {
+    adjacency: Vec<HashSet<NodeId>>,
+    stack: Vec<Frame>,
+}
+
+impl MaximalCliques {
+    fn frame(&self, clique: Vec<NodeId>, candidates: Vec<NodeId>, excluded: Vec<NodeId>) -> Frame {
+        // Branching on the neighbors of the pivot is useless, as any clique they
+        // are in can be extended with the pivot itself
+        let pivot = candidates.iter().chain(&excluded).max_by_key(|u| {
+            candidates
+                .iter()
+                .filter(|v| self.adjacency[**u].contains(v))
+                .count()
+        });
+        let branches = match pivot {
+            Some(pivot) => candidates
+                .iter()
+                .copied()
+                .filter(|v| !self.adjacency[*pivot].contains(v))
+                .collect(),
+            None => vec![],
+        };
+
+        Frame {
+            clique,
+            candidates,
+            excluded,
+            branches,
+        }
+    }
+}
+
+impl Iterator for MaximalCliques {
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(frame) = self.stack.last_mut() {
+            let v = match frame.branches.pop() {
+                Some(v) => v,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let neighbors = &self.adjacency[v];
+            let mut clique = frame.clique.clone();
+            clique.push(v);
+            let candidates: Vec<NodeId> = frame
+                .candidates
+                .iter()
+                .copied()
+                .filter(|u| neighbors.contains(u))
+                .collect();
+            let excluded: Vec<NodeId> = frame
+                .excluded
+                .iter()
+                .copied()
+                .filter(|u| neighbors.contains(u))
+                .collect();
+            frame.candidates.retain(|u| *u != v);
+            frame.excluded.push(v);
+
+            if candidates.is_empty() {
+                if excluded.is_empty() {
+                    clique.sort_unstable();
+                    return Some(clique);
+                }
+            } else {
+                let frame = self.frame(clique, candidates, excluded);
+                self.stack.push(frame);
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns an iterator over the maximal cliques of `graph`, each one sorted
+///
+/// Panics if the graph is directed.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{cliques::maximal_cliques, generators};
+/// let mut cliques: Vec<_> = maximal_cliques(&generators::wheel(5)).collect();
+/// cliques.sort();
+/// assert_eq!(cliques, vec![vec![0, 1, 2], vec![0, 1, 4], vec![0, 2, 3], vec![0, 3, 4]]);
+/// ```
+pub fn maximal_cliques<G: IntoNeighbors + NodeCount>(graph: &G) -> MaximalCliques {
+    let mut cliques = MaximalCliques {
+        adjacency: adjacency(graph),
+        stack: vec![],
+    };
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    if !nodes.is_empty() {
+        let frame = cliques.frame(vec![], nodes, vec![]);
+        cliques.stack.push(frame);
+    }
+
+    cliques
+}
+
+/// Finds a clique with as many nodes as possible, using branch and bound
+///
+/// Panics if the graph is directed.
+pub fn max_clique<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Vec<NodeId>, TooManyNodes> {
+    let (adjacency, nodes) = bit_adjacency(graph)?;
+    Ok(nodes_of(max_clique_mask(&adjacency, nodes)))
+}
+
+/// Finds an independent set (a set of nodes with no edge between any two of
+/// them) with as many nodes as possible, as the largest clique of the complement
+///
+/// Panics if the graph is directed.
+pub fn maximum_independent_set<G: IntoNeighbors + NodeCount>(
+    graph: &G,
+) -> Result<Vec<NodeId>, TooManyNodes> {
+    let (adjacency, nodes) = bit_adjacency(graph)?;
+    let complement: Vec<u64> = adjacency
+        .iter()
+        .enumerate()
+        .map(|(node, mask)| nodes & !mask & !(1 << node))
+        .collect();
+
+    Ok(nodes_of(max_clique_mask(&complement, nodes)))
+}
+
+/// Builds an independent set by repeatedly taking the node with the fewest
+/// remaining neighbors and discarding those neighbors. Fast, but not always maximum.
+///
+/// Panics if the graph is directed.
+pub fn greedy_independent_set<G: IntoNeighbors + NodeCount>(graph: &G) -> Vec<NodeId> {
+    let adjacency = adjacency(graph);
+    let mut alive: Vec<bool> = (0..adjacency.len())
+        .map(|node| graph.contains_node(node))
+        .collect();
+    let mut set = vec![];
+    loop {
+        let next = (0..adjacency.len())
+            .filter(|node| alive[*node])
+            .min_by_key(|node| adjacency[*node].iter().filter(|n| alive[**n]).count());
+        let node = match next {
+            Some(node) => node,
+            None => break,
+        };
+
+        set.push(node);
+        alive[node] = false;
+        for neighbor in &adjacency[node] {
+            alive[*neighbor] = false;
+        }
+    }
+
+    set.sort_unstable();
+    set
+}
+
+/// Branch and bound search bounded by a greedy coloring of the candidates, since
+/// a clique can take at most one node of each color. Only the nodes in `nodes`
+/// are considered.
+fn max_clique_mask(adjacency: &[u64], nodes: u64) -> u64 {
+    fn expand(adjacency: &[u64], clique: u64, mut candidates: u64, best: &mut u64) {
+        let mut colored = vec![];
+        let mut uncolored = candidates;
+        let mut color = 0;
+        while uncolored != 0 {
+            color += 1;
+            let mut available = uncolored;
+            while available != 0 {
+                let v = available.trailing_zeros() as usize;
+                available &= !(1 << v) & !adjacency[v];
+                uncolored &= !(1 << v);
+                colored.push((v, color));
+            }
+        }
+
+        for (v, color) in colored.into_iter().rev() {
+            if clique.count_ones() + color <= best.count_ones() {
+                return;
+            }
+
+            let next = candidates & adjacency[v];
+            if next == 0 {
+                if clique.count_ones() + 1 > best.count_ones() {
+                    *best = clique | 1 << v;
+                }
+            } else {
+                expand(adjacency, clique | 1 << v, next, best);
+            }
+            candidates &= !(1 << v);
+        }
+    }
+
+    let mut best = 0;
+    expand(adjacency, 0, nodes, &mut best);
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+
+    #[test]
+    fn maximal_cliques_of_small_graphs() {
+        let mut graph = generators::path(3);
+        graph.add_node(());
+        let mut cliques: Vec<_> = maximal_cliques(&graph).collect();
+        cliques.sort();
+        assert_eq!(cliques, vec![vec![0, 1], vec![1, 2], vec![3]]);
+        assert_eq!(maximal_cliques(&generators::complete(5)).count(), 1);
+        assert_eq!(maximal_cliques(&Graph::<()>::new_undirected()).count(), 0);
+    }
+
+    #[test]
+    fn hidden_nodes() {
+        let graph = generators::star(5);
+        let leaves = graph.filter_nodes(|node| node.id() != 0);
+        assert_eq!(maximal_cliques(&leaves).count(), 4);
+        assert_eq!(max_clique(&leaves).unwrap().len(), 1);
+        assert_eq!(maximum_independent_set(&leaves), Ok(vec![1, 2, 3, 4]));
+
+        let graph = generators::complete(4);
+        let triangle = graph.filter_nodes(|node| node.id() != 1);
+        assert_eq!(maximum_independent_set(&triangle).unwrap().len(), 1);
+        assert_eq!(greedy_independent_set(&triangle), vec![0]);
+    }
+
+    #[test]
+    fn petersen_has_no_triangles() {
+        let petersen = generators::petersen();
+        assert!(maximal_cliques(&petersen).all(|c| c.len() == 2));
+        assert_eq!(max_clique(&petersen).unwrap().len(), 2);
+        assert_eq!(maximum_independent_set(&petersen).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn independent_sets() {
+        assert_eq!(
+            maximum_independent_set(&generators::star(5)),
+            Ok(vec![1, 2, 3, 4])
+        );
+        assert_eq!(greedy_independent_set(&generators::path(5)), vec![0, 2, 4]);
+        assert_eq!(max_clique(&generators::wheel(6)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn exact_searches_are_guarded() {
+        let graph = generators::path(EXACT_LIMIT + 1);
+        let error = TooManyNodes {
+            nodes: EXACT_LIMIT + 1,
+            limit: EXACT_LIMIT,
+        };
+        assert_eq!(max_clique(&graph), Err(error));
+        assert_eq!(maximum_independent_set(&graph), Err(error));
+        assert_eq!(
+            maximum_independent_set(&generators::path(64))
+                .unwrap()
+                .len(),
+            32
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Vertex and edge coloring of undirected graphs. Colors are numbered from `0`
+//! and self loops are ignored.
+
+use std::collections::HashSet;
+
+use super::{EdgeId, Graph};
+use crate::{
+    algo::TooManyNodes,
+    cliques,
+    prelude::{IntoNeighbors, NodeCount, NodeId},
+};
+
+/// The largest graph accepted by `chromatic_number`
+pub const EXACT_LIMIT: usize = 32;
+
+/// The order in which the greedy coloring visits nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColoringStrategy {
+    /// Nodes with more neighbors come first
+    LargestFirst,
+    /// Nodes are ordered by repeatedly removing the one with the fewest remaining
+    /// neighbors, then colored in reverse removal order
+    SmallestLast,
+    /// The next node is always the one whose neighbors already use the most
+    /// distinct colors
+    DSatur,
+}
+
+fn adjacency<G: IntoNeighbors + NodeCount>(graph: &G) -> Vec<Vec<NodeId>> {
+    assert!(!graph.is_directed(), "Coloring needs an undirected graph");
+    (0..graph.node_count())
+        .map(|node| {
+            let mut neighbors: Vec<NodeId> = graph.neighbors(node).filter(|n| *n != node).collect();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors
+        })
+        .collect()
+}
+
+fn smallest_free(adjacency: &[Vec<NodeId>], colors: &[Option<usize>], node: NodeId) -> usize {
+    let used: HashSet<usize> = adjacency[node].iter().filter_map(|n| colors[*n]).collect();
+    (0..).find(|c| !used.contains(c)).unwrap()
+}
+
+fn saturation(adjacency: &[Vec<NodeId>], colors: &[Option<usize>], node: NodeId) -> usize {
+    let used: HashSet<usize> = adjacency[node].iter().filter_map(|n| colors[*n]).collect();
+    used.len()
+}
+
+fn smallest_last_order(adjacency: &[Vec<NodeId>]) -> Vec<NodeId> {
+    let n = adjacency.len();
+    let mut degrees: Vec<usize> = adjacency.iter().map(|a| a.len()).collect();
+    let mut removed = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    for _ in 0..n {
+        let node = (0..n)
+            .filter(|node| !removed[*node])
+            .min_by_key(|node| degrees[*node])
+            .unwrap();
+        removed[node] = true;
+        order.push(node);
+        for neighbor in &adjacency[node] {
+            degrees[*neighbor] -= 1;
+        }
+    }
+
+    order.reverse();
+    order
+}
+
+/// Colors every node with the smallest color not used by its neighbors, visiting
+/// nodes in the order given by `strategy`
+///
+/// Panics if the graph is directed.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{coloring::*, generators};
+/// let graph = generators::cycle(5);
+/// let colors = greedy_coloring(&graph, ColoringStrategy::DSatur);
+/// assert!(is_proper_coloring(&graph, &colors));
+/// assert_eq!(colors.iter().max(), Some(&2));
+/// ```
+pub fn greedy_coloring<G: IntoNeighbors + NodeCount>(
+    graph: &G,
+    strategy: ColoringStrategy,
+) -> Vec<usize> {
+    let adjacency = adjacency(graph);
+    let n = adjacency.len();
+    let mut colors = vec![None; n];
+
+    match strategy {
+        ColoringStrategy::LargestFirst | ColoringStrategy::SmallestLast => {
+            let order = if strategy == ColoringStrategy::LargestFirst {
+                let mut order: Vec<NodeId> = (0..n).collect();
+                order.sort_by_key(|node| std::cmp::Reverse(adjacency[*node].len()));
+                order
+            } else {
+                smallest_last_order(&adjacency)
+            };
+            for node in order {
+                colors[node] = Some(smallest_free(&adjacency, &colors, node));
+            }
+        }
+        ColoringStrategy::DSatur => {
+            for _ in 0..n {
+                let node = (0..n)
+                    .filter(|node| colors[*node].is_none())
+                    .max_by_key(|node| {
+                        let s = saturation(&adjacency, &colors, *node);
+                        (s, adjacency[*node].len(), std::cmp::Reverse(*node))
+                    })
+                    .unwrap();
+                colors[node] = Some(smallest_free(&adjacency, &colors, node));
+            }
+        }
+    }
+
+    colors.into_iter().map(Option::unwrap).collect()
+}
+
+/// Finds the smallest number of colors needed to color `graph`, along with a
+/// coloring that uses them, by a DSatur based branch and bound search
+///
+/// Panics if the graph is directed.
+pub fn chromatic_number<G: IntoNeighbors + NodeCount>(
+    graph: &G,
+) -> Result<(usize, Vec<usize>), TooManyNodes> {
+    let n = graph.node_count();
+    if n > EXACT_LIMIT {
+        return Err(TooManyNodes {
+            nodes: n,
+            limit: EXACT_LIMIT,
+        });
+    }
+
+    let adjacency = adjacency(graph);
+    let greedy = greedy_coloring(graph, ColoringStrategy::DSatur);
+    let mut best = (greedy.iter().max().map_or(0, |c| c + 1), greedy);
+    // No coloring can use fewer colors than the size of a clique
+    let lower = cliques::max_clique(graph).map_or(1, |c| c.len());
+    if best.0 > lower {
+        let mut colors = vec![None; n];
+        search(&adjacency, &mut colors, 0, 0, lower, &mut best);
+    }
+
+    Ok(best)
+}
+
+fn search(
+    adjacency: &[Vec<NodeId>],
+    colors: &mut [Option<usize>],
+    colored: usize,
+    used: usize,
+    lower: usize,
+    best: &mut (usize, Vec<usize>),
+) -> bool {
+    let n = adjacency.len();
+    if colored == n {
+        *best = (used, colors.iter().map(|c| c.unwrap()).collect());
+        return used == lower;
+    }
+
+    let node = (0..n)
+        .filter(|node| colors[*node].is_none())
+        .max_by_key(|node| (saturation(adjacency, colors, *node), adjacency[*node].len()))
+        .unwrap();
+    for color in 0..=used {
+        // Only colors that keep the total below the best coloring found are worth trying
+        if used.max(color + 1) >= best.0 {
+            break;
+        }
+        if adjacency[node].iter().any(|n| colors[*n] == Some(color)) {
+            continue;
+        }
+
+        colors[node] = Some(color);
+        if search(
+            adjacency,
+            colors,
+            colored + 1,
+            used.max(color + 1),
+            lower,
+            best,
+        ) {
+            return true;
+        }
+        colors[node] = None;
+    }
+
+    false
+}
+
+/// Returns `true` if no two neighbors share a color
+pub fn is_proper_coloring<G: IntoNeighbors + NodeCount>(graph: &G, colors: &[usize]) -> bool {
+    colors.len() == graph.node_count()
+        && (0..graph.node_count()).all(|node| {
+            graph
+                .neighbors(node)
+                .all(|n| n == node || colors[n] != colors[node])
+        })
+}
+
+/// Colors every edge with the smallest color not used by the other edges on
+/// its endpoints. Returns the color of each edge, indexed by edge id.
+///
+/// This takes a `Graph` rather than any `IntoEdges` because the result is
+/// indexed by edge id, and only `Graph` numbers its edges densely from `0`.
+///
+/// Panics if the graph is directed.
+pub fn greedy_edge_coloring<T>(graph: &Graph<T>) -> Vec<usize> {
+    assert!(!graph.is_directed(), "Coloring needs an undirected graph");
+
+    let mut colors: Vec<Option<usize>> = vec![None; graph.edge_count()];
+    for (id, edge) in graph.edges().enumerate() {
+        if edge.from() == edge.to() {
+            colors[id] = Some(0);
+            continue;
+        }
+
+        let used: HashSet<usize> = incident(graph, edge.from())
+            .chain(incident(graph, edge.to()))
+            .filter_map(|e| colors[e])
+            .collect();
+        colors[id] = (0..).find(|c| !used.contains(c));
+    }
+
+    colors.into_iter().map(Option::unwrap).collect()
+}
+
+/// Returns `true` if no two edges sharing an endpoint share a color. Takes a
+/// `Graph` for the same reason as `greedy_edge_coloring`.
+pub fn is_proper_edge_coloring<T>(graph: &Graph<T>, colors: &[usize]) -> bool {
+    colors.len() == graph.edge_count()
+        && (0..graph.node_count()).all(|node| {
+            let mut seen = HashSet::new();
+            incident(graph, node).all(|e| seen.insert(colors[e]))
+        })
+}
+
+/// Edges touching `node`, skipping self loops
+fn incident<T>(graph: &Graph<T>, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
+    graph
+        .node(node)
+        .unwrap()
+        .edges()
+        .iter()
+        .copied()
+        .filter(move |e| {
+            let edge = graph.edge(*e).unwrap();
+            edge.from() != edge.to()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+
+    #[test]
+    fn greedy_strategies() {
+        let graph = generators::petersen();
+        for strategy in [
+            ColoringStrategy::LargestFirst,
+            ColoringStrategy::SmallestLast,
+            ColoringStrategy::DSatur,
+        ]
+        .iter()
+        {
+            let colors = greedy_coloring(&graph, *strategy);
+            assert!(is_proper_coloring(&graph, &colors));
+        }
+
+        let bipartite = generators::grid(3, 3);
+        let colors = greedy_coloring(&bipartite, ColoringStrategy::DSatur);
+        assert_eq!(colors.iter().max(), Some(&1));
+    }
+
+    #[test]
+    fn exact_chromatic_number() {
+        let (k, colors) = chromatic_number(&generators::petersen()).unwrap();
+        assert_eq!(k, 3);
+        assert!(is_proper_coloring(&generators::petersen(), &colors));
+        assert_eq!(chromatic_number(&generators::wheel(6)).unwrap().0, 4);
+        assert_eq!(chromatic_number(&generators::complete(5)).unwrap().0, 5);
+        assert!(chromatic_number(&generators::path(EXACT_LIMIT + 1)).is_err());
+    }
+
+    #[test]
+    fn improper_colorings() {
+        let graph = generators::path(3);
+        assert!(!is_proper_coloring(&graph, &[0, 0, 1]));
+        assert!(!is_proper_coloring(&graph, &[0, 1]));
+    }
+
+    #[test]
+    fn edge_coloring() {
+        let graph = generators::star(5);
+        let colors = greedy_edge_coloring(&graph);
+        assert!(is_proper_edge_coloring(&graph, &colors));
+        assert_eq!(colors, vec![0, 1, 2, 3]);
+        assert!(!is_proper_edge_coloring(&graph, &[0, 1, 1, 2]));
+    }
+}
@@ -0,0 +1,411 @@
+//! Community detection on weighted undirected graphs. Partitions are vectors
+//! holding the community of each node, numbered from `0` in order of their
+//! smallest node. Directed graphs can be analysed through `undirected_view()`.
+//! Parallel edges add up their weights.
+
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::{Graph, NodeId};
+use crate::prelude::{IntoEdges, NodeCount};
+
+/// Renumbers the communities of `partition` from `0`, in order of their smallest node
+pub fn relabel(partition: &[usize]) -> Vec<usize> {
+    let mut labels = HashMap::new();
+    partition
+        .iter()
+        .map(|c| {
+            let next = labels.len();
+            *labels.entry(*c).or_insert(next)
+        })
+        .collect()
+}
+
+/// Groups the nodes by community, in order of their smallest node
+pub fn groups(partition: &[usize]) -> Vec<Vec<NodeId>> {
+    let labels = relabel(partition);
+    let mut groups = vec![vec![]; labels.iter().max().map_or(0, |c| c + 1)];
+    for (node, community) in labels.into_iter().enumerate() {
+        groups[community].push(node);
+    }
+
+    groups
+}
+
+/// Collapses every community of `partition` into a single node holding its
+/// members. Edges between communities are merged, summing their weights, and
+/// the edges inside a community become a self loop. On directed graphs edges
+/// going both ways between two communities are merged too.
+pub fn community_graph<G: IntoEdges + NodeCount>(
+    graph: &G,
+    partition: &[usize],
+) -> Graph<Vec<NodeId>> {
+    let labels = relabel(partition);
+    let mut quotient = Graph::new_undirected();
+    for members in groups(&labels) {
+        quotient.add_node(members);
+    }
+
+    let mut weights: HashMap<(usize, usize), usize> = HashMap::new();
+    for node in 0..graph.node_count() {
+        // Undirected edges are seen from both ends, count them from the smaller one
+        let edges = graph
+            .edges(node)
+            .filter(|e| graph.is_directed() || e.source <= e.target);
+        for edge in edges {
+            let (a, b) = (labels[edge.source], labels[edge.target]);
+            *weights.entry((a.min(b), a.max(b))).or_insert(0) += edge.weight;
+        }
+    }
+    let mut weights: Vec<_> = weights.into_iter().collect();
+    weights.sort_unstable();
+    for ((a, b), weight) in weights {
+        quotient.add_edge(a, b, weight);
+    }
+
+    quotient
+}
+
+/// The modularity of `partition`: the fraction of the edge weight that falls
+/// inside communities, minus the fraction expected if edges were placed at random
+/// while keeping node degrees.
+///
+/// Panics if the graph is directed.
+pub fn modularity<G: IntoEdges + NodeCount>(graph: &G, partition: &[usize]) -> f64 {
+    assert!(!graph.is_directed(), "Modularity needs an undirected graph");
+
+    let level = Level::from_graph(graph);
+    let total = level.total_weight();
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    let labels = relabel(partition);
+    let k = labels.iter().max().map_or(0, |c| c + 1);
+    let mut inside = vec![0.0; k];
+    let mut degrees = vec![0.0; k];
+    for node in 0..level.len() {
+        let community = labels[node];
+        degrees[community] += level.degree(node);
+        inside[community] += level.loops[node];
+        for (next, weight) in &level.adjacency[node] {
+            if labels[*next] == community {
+                // Every edge is seen once from each side
+                inside[community] += weight / 2.0;
+            }
+        }
+    }
+
+    (0..k)
+        .map(|c| inside[c] / total - (degrees[c] / (2.0 * total)).powi(2))
+        .sum()
+}
+
+/// Finds communities with the Louvain method: nodes greedily move to the
+/// neighboring community that most increases modularity, then communities are
+/// collapsed into single nodes and the process repeats until nothing moves.
+///
+/// Panics if the graph is directed.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, community::louvain};
+/// let mut graph = Graph::new_undirected();
+/// for _ in 0..6 {
+///     graph.add_node(());
+/// }
+/// for (a, b) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)].iter() {
+///     graph.add_edge(*a, *b, 1);
+/// }
+/// assert_eq!(louvain(&graph), vec![0, 0, 0, 1, 1, 1]);
+/// ```
+pub fn louvain<G: IntoEdges + NodeCount>(graph: &G) -> Vec<usize> {
+    assert!(!graph.is_directed(), "Louvain needs an undirected graph");
+
+    let mut level = Level::from_graph(graph);
+    let mut partition: Vec<usize> = (0..graph.node_count()).collect();
+    loop {
+        let (communities, moved) = level.one_level();
+        if !moved {
+            break;
+        }
+
+        for community in partition.iter_mut() {
+            *community = communities[*community];
+        }
+        level = level.aggregate(&communities);
+    }
+
+    relabel(&partition)
+}
+
+/// Finds communities with asynchronous label propagation: visiting nodes in
+/// random order, each one takes the label carrying the most edge weight among
+/// its neighbors, until every node agrees with its neighborhood.
+///
+/// Panics if the graph is directed.
+pub fn label_propagation<G: IntoEdges + NodeCount, R: Rng>(graph: &G, rng: &mut R) -> Vec<usize> {
+    assert!(
+        !graph.is_directed(),
+        "Label propagation needs an undirected graph"
+    );
+
+    let n = graph.node_count();
+    let mut labels: Vec<usize> = (0..n).collect();
+    let mut order: Vec<NodeId> = (0..n).collect();
+    let mut stable = false;
+    while !stable {
+        stable = true;
+        order.shuffle(rng);
+        for &node in &order {
+            let mut weights: HashMap<usize, usize> = HashMap::new();
+            for edge in graph.edges(node).filter(|e| e.target != node) {
+                *weights.entry(labels[edge.target]).or_insert(0) += edge.weight;
+            }
+
+            let best = match weights.values().max() {
+                Some(best) => *best,
+                None => continue,
+            };
+            if weights.get(&labels[node]) == Some(&best) {
+                continue;
+            }
+
+            let mut candidates: Vec<usize> = weights
+                .into_iter()
+                .filter(|(_, w)| *w == best)
+                .map(|(label, _)| label)
+                .collect();
+            candidates.sort_unstable();
+            labels[node] = *candidates.choose(rng).unwrap();
+            stable = false;
+        }
+    }
+
+    relabel(&labels)
+}
+
+/// A weighted undirected graph collapsed by the Louvain method
+struct Level {
+    adjacency: Vec<Vec<(usize, f64)>>,
+    loops: Vec<f64>,
+}
+
+impl Level {
+    fn from_graph<G: IntoEdges + NodeCount>(graph: &G) -> Self {
+        let n = graph.node_count();
+        let mut level = Level {
+            adjacency: vec![vec![]; n],
+            loops: vec![0.0; n],
+        };
+        for node in 0..n {
+            let mut merged: HashMap<usize, f64> = HashMap::new();
+            for edge in graph.edges(node) {
+                if edge.target == node {
+                    level.loops[node] += edge.weight as f64;
+                } else {
+                    *merged.entry(edge.target).or_insert(0.0) += edge.weight as f64;
+                }
+            }
+            let mut merged: Vec<_> = merged.into_iter().collect();
+            merged.sort_unstable_by_key(|(next, _)| *next);
+            level.adjacency[node] = merged;
+        }
+
+        level
+    }
+
+    fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Self loops count twice, as both of their ends touch the node
+    fn degree(&self, node: usize) -> f64 {
+        self.adjacency[node].iter().map(|(_, w)| w).sum::<f64>() + 2.0 * self.loops[node]
+    }
+
+    fn total_weight(&self) -> f64 {
+        (0..self.len()).map(|node| self.degree(node)).sum::<f64>() / 2.0
+    }
+
+    /// Moves nodes between communities until modularity stops improving. Returns
+    /// the community of each node, numbered from `0`, and whether anything moved.
+    fn one_level(&self) -> (Vec<usize>, bool) {
+        let n = self.len();
+        let m2 = 2.0 * self.total_weight();
+        let degrees: Vec<f64> = (0..n).map(|node| self.degree(node)).collect();
+        let mut communities: Vec<usize> = (0..n).collect();
+        let mut totals = degrees.clone();
+        let mut moved = false;
+        if m2 == 0.0 {
+            return (communities, false);
+        }
+
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for node in 0..n {
+                let current = communities[node];
+                totals[current] -= degrees[node];
+
+                let mut links: HashMap<usize, f64> = HashMap::new();
+                links.insert(current, 0.0);
+                for (next, weight) in &self.adjacency[node] {
+                    *links.entry(communities[*next]).or_insert(0.0) += weight;
+                }
+
+                let gain =
+                    |community: usize, weight: f64| weight - totals[community] * degrees[node] / m2;
+                let mut best = (current, gain(current, links[&current]));
+                let mut candidates: Vec<_> = links.into_iter().collect();
+                candidates.sort_unstable_by_key(|(c, _)| *c);
+                for (community, weight) in candidates {
+                    let g = gain(community, weight);
+                    if g > best.1 + 1e-12 {
+                        best = (community, g);
+                    }
+                }
+
+                totals[best.0] += degrees[node];
+                if best.0 != current {
+                    communities[node] = best.0;
+                    improved = true;
+                    moved = true;
+                }
+            }
+        }
+
+        (relabel(&communities), moved)
+    }
+
+    /// Collapses every community into a single node
+    fn aggregate(&self, communities: &[usize]) -> Level {
+        let k = communities.iter().max().map_or(0, |c| c + 1);
+        let mut merged: Vec<HashMap<usize, f64>> = vec![HashMap::new(); k];
+        let mut loops = vec![0.0; k];
+        for node in 0..self.len() {
+            let community = communities[node];
+            loops[community] += self.loops[node];
+            for (next, weight) in &self.adjacency[node] {
+                if communities[*next] == community {
+                    loops[community] += weight / 2.0;
+                } else {
+                    *merged[community].entry(communities[*next]).or_insert(0.0) += weight;
+                }
+            }
+        }
+
+        let adjacency = merged
+            .into_iter()
+            .map(|links| {
+                let mut links: Vec<_> = links.into_iter().collect();
+                links.sort_unstable_by_key(|(next, _)| *next);
+                links
+            })
+            .collect();
+        Level { adjacency, loops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn two_triangles() -> Graph {
+        let mut graph = Graph::new_undirected();
+        for _ in 0..6 {
+            graph.add_node(());
+        }
+        for (a, b) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)].iter() {
+            graph.add_edge(*a, *b, 1);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn modularity_of_partitions() {
+        let graph = two_triangles();
+        let split = modularity(&graph, &[0, 0, 0, 1, 1, 1]);
+        assert!((split - (2.0 * (3.0 / 7.0 - 0.25))).abs() < 1e-9);
+        assert!(modularity(&graph, &[0; 6]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn louvain_ring_of_cliques() {
+        // Six 4-cliques joined in a ring by single edges
+        let mut graph = Graph::new_undirected();
+        for _ in 0..24 {
+            graph.add_node(());
+        }
+        for clique in 0..6 {
+            for a in 0..4 {
+                for b in a + 1..4 {
+                    graph.add_edge(clique * 4 + a, clique * 4 + b, 1);
+                }
+            }
+            graph.add_edge(clique * 4 + 3, (clique * 4 + 4) % 24, 1);
+        }
+
+        let partition = louvain(&graph);
+        assert_eq!(partition, (0..24).map(|n| n / 4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn label_propagation_splits_components() {
+        let mut graph = generators::complete(4);
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        for a in 4..8 {
+            for b in a + 1..8 {
+                graph.add_edge(a, b, 1);
+            }
+        }
+
+        let partition = label_propagation(&graph, &mut StdRng::seed_from_u64(5));
+        assert_eq!(partition, vec![0, 0, 0, 0, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "undirected graph")]
+    fn modularity_rejects_directed_graphs() {
+        modularity(&Graph::<()>::new(), &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "undirected graph")]
+    fn label_propagation_rejects_directed_graphs() {
+        label_propagation(&Graph::<()>::new(), &mut StdRng::seed_from_u64(1));
+    }
+
+    #[test]
+    fn grouping() {
+        let graph = two_triangles();
+        let partition = [7, 7, 7, 2, 2, 2];
+        assert_eq!(groups(&partition), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+
+        let quotient = community_graph(&graph, &partition);
+        assert_eq!(quotient.node_count(), 2);
+        let weights: Vec<_> = quotient
+            .edges()
+            .map(|e| (e.from(), e.to(), e.weight()))
+            .collect();
+        assert_eq!(weights, vec![(0, 0, 3), (0, 1, 1), (1, 1, 3)]);
+
+        let mut directed = Graph::new();
+        for _ in 0..6 {
+            directed.add_node(());
+        }
+        for edge in graph.edges() {
+            directed.add_edge(edge.from(), edge.to(), edge.weight());
+        }
+        let quotient = community_graph(&directed, &partition);
+        assert_eq!(quotient.edges().map(|e| e.weight()).sum::<usize>(), 7);
+        let quotient = community_graph(&directed.undirected_view(), &partition);
+        assert_eq!(quotient.edges().map(|e| e.weight()).sum::<usize>(), 7);
+    }
+}
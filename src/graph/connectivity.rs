@@ -0,0 +1,340 @@
+//! Minimum cuts and connectivity. Edge weights are used as capacities, so
+//! parallel edges add up their capacities, and each of them has to be cut.
+//! Hidden nodes are left out, as if they were not part of the graph.
+
+use std::collections::VecDeque;
+
+use crate::prelude::{IntoEdges, IntoNeighbors, NodeCount, NodeId};
+
+/// A residual network for augmenting path searches
+#[derive(Clone)]
+struct Network {
+    /// Outgoing arcs of each node, as indices into `heads` and `capacities`
+    arcs: Vec<Vec<usize>>,
+    heads: Vec<NodeId>,
+    capacities: Vec<usize>,
+}
+
+impl Network {
+    fn new(n: usize) -> Self {
+        Self {
+            arcs: vec![vec![]; n],
+            heads: vec![],
+            capacities: vec![],
+        }
+    }
+
+    /// Adds an arc and its reverse, which is stored right after it
+    fn add(&mut self, from: NodeId, to: NodeId, capacity: usize, reverse_capacity: usize) {
+        self.arcs[from].push(self.heads.len());
+        self.heads.push(to);
+        self.capacities.push(capacity);
+        self.arcs[to].push(self.heads.len());
+        self.heads.push(from);
+        self.capacities.push(reverse_capacity);
+    }
+
+    fn from_graph<G: IntoEdges + NodeCount>(graph: &G, unit: bool) -> Self {
+        let mut network = Network::new(graph.node_count());
+        for node in 0..graph.node_count() {
+            for edge in graph.edges(node) {
+                let capacity = if unit { 1 } else { edge.weight };
+                if graph.is_directed() {
+                    network.add(node, edge.target, capacity, 0);
+                } else if node < edge.target {
+                    // Undirected edges are seen from both ends, keep one copy
+                    network.add(node, edge.target, capacity, capacity);
+                }
+            }
+        }
+
+        network
+    }
+
+    /// Edmonds–Karp: repeatedly augments along the shortest residual path
+    fn max_flow(&mut self, source: NodeId, sink: NodeId) -> usize {
+        if source == sink {
+            return 0;
+        }
+
+        let mut total = 0;
+        loop {
+            let mut via: Vec<Option<usize>> = vec![None; self.arcs.len()];
+            let mut queue = VecDeque::new();
+            queue.push_back(source);
+            while let Some(node) = queue.pop_front() {
+                for &arc in &self.arcs[node] {
+                    let head = self.heads[arc];
+                    if self.capacities[arc] > 0 && head != source && via[head].is_none() {
+                        via[head] = Some(arc);
+                        queue.push_back(head);
+                    }
+                }
+            }
+
+            if via[sink].is_none() {
+                return total;
+            }
+
+            let mut path = vec![];
+            let mut node = sink;
+            while let Some(arc) = via[node] {
+                path.push(arc);
+                node = self.heads[arc ^ 1];
+            }
+            let amount = path.iter().map(|arc| self.capacities[*arc]).min().unwrap();
+            for arc in path {
+                self.capacities[arc] -= amount;
+                self.capacities[arc ^ 1] += amount;
+            }
+            total += amount;
+        }
+    }
+}
+
+/// The maximum flow that can go from `source` to `sink`, using edge weights as
+/// capacities. Found with the Edmonds–Karp augmenting path algorithm.
+pub fn max_flow<G: IntoEdges + NodeCount>(graph: &G, source: NodeId, sink: NodeId) -> usize {
+    Network::from_graph(graph, false).max_flow(source, sink)
+}
+
+/// Finds the global minimum cut of a weighted undirected graph with the
+/// Stoer–Wagner algorithm. Returns the total weight of the cut edges and the
+/// nodes on one side of the cut, sorted.
+///
+/// Panics if the graph is directed or has less than two nodes.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, connectivity::stoer_wagner_min_cut};
+/// let mut graph = Graph::new_undirected();
+/// for _ in 0..4 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 5);
+/// graph.add_edge(1, 2, 1);
+/// graph.add_edge(2, 3, 5);
+/// graph.add_edge(3, 0, 2);
+/// assert_eq!(stoer_wagner_min_cut(&graph), (3, vec![2, 3]));
+/// ```
+pub fn stoer_wagner_min_cut<G: IntoEdges + NodeCount>(graph: &G) -> (usize, Vec<NodeId>) {
+    assert!(
+        !graph.is_directed(),
+        "Minimum cuts need an undirected graph"
+    );
+    let n = graph.node_count();
+    let mut active: Vec<NodeId> = graph.node_ids().collect();
+    assert!(active.len() >= 2, "Minimum cuts need at least two nodes");
+
+    let mut weights = vec![vec![0usize; n]; n];
+    for (node, row) in weights.iter_mut().enumerate() {
+        for edge in graph.edges(node).filter(|e| e.target != node) {
+            row[edge.target] += edge.weight;
+        }
+    }
+
+    let mut merged: Vec<Vec<NodeId>> = (0..n).map(|node| vec![node]).collect();
+    let mut best = (usize::MAX, vec![]);
+    while active.len() > 1 {
+        // Maximum adjacency ordering: the last two nodes added are the ones merged
+        let mut added = vec![false; n];
+        let mut connection = vec![0usize; n];
+        let mut previous = active[0];
+        let mut last = active[0];
+        for _ in 0..active.len() {
+            previous = last;
+            last = *active
+                .iter()
+                .filter(|node| !added[**node])
+                .max_by_key(|node| (connection[**node], std::cmp::Reverse(**node)))
+                .unwrap();
+            added[last] = true;
+            for &node in &active {
+                connection[node] += weights[last][node];
+            }
+        }
+
+        let cut = connection[last];
+        if cut < best.0 {
+            best = (cut, merged[last].clone());
+        }
+
+        let moved = std::mem::take(&mut merged[last]);
+        merged[previous].extend(moved);
+        for &node in &active {
+            weights[previous][node] += weights[last][node];
+            weights[node][previous] = weights[previous][node];
+        }
+        weights[previous][previous] = 0;
+        active.retain(|node| *node != last);
+    }
+
+    best.1.sort_unstable();
+    best
+}
+
+/// The smallest number of edges to remove to disconnect the graph. On directed
+/// graphs, the result is the number needed to break strong connectivity.
+pub fn edge_connectivity<G: IntoEdges + NodeCount>(graph: &G) -> usize {
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    let network = Network::from_graph(graph, true);
+    let flow = |source, sink| network.clone().max_flow(source, sink);
+
+    nodes
+        .iter()
+        .skip(1)
+        .map(|&node| {
+            if graph.is_directed() {
+                flow(nodes[0], node).min(flow(node, nodes[0]))
+            } else {
+                flow(nodes[0], node)
+            }
+        })
+        .min()
+        .unwrap_or(0)
+}
+
+/// The smallest number of nodes to remove to disconnect the graph (or to break
+/// strong connectivity, on directed graphs). A complete graph on `n` nodes has
+/// connectivity `n - 1`.
+pub fn node_connectivity<G: IntoNeighbors + NodeCount>(graph: &G) -> usize {
+    let n = graph.node_count();
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    let adjacent = |a: NodeId, b: NodeId| {
+        graph.neighbors(a).any(|x| x == b)
+            || (!graph.is_directed() && graph.neighbors(b).any(|x| x == a))
+    };
+
+    // Every node is split in an entry and an exit joined by a unit arc, so that
+    // a flow can only cross it once
+    let mut network = Network::new(2 * n);
+    for node in 0..n {
+        network.add(2 * node, 2 * node + 1, 1, 0);
+        for next in graph.neighbors(node).filter(|next| *next != node) {
+            network.add(2 * node + 1, 2 * next, n, 0);
+        }
+    }
+    let local = |source: NodeId, sink: NodeId| network.clone().max_flow(2 * source + 1, 2 * sink);
+
+    let pairs: Vec<(NodeId, NodeId)> = if graph.is_directed() {
+        nodes
+            .iter()
+            .flat_map(|a| nodes.iter().map(move |b| (*a, *b)))
+            .filter(|(a, b)| a != b)
+            .collect()
+    } else {
+        // Any minimum separator either avoids the node of minimum degree, and so
+        // splits it from some other node, or contains it, and so splits two of
+        // its neighbors
+        let min = match nodes
+            .iter()
+            .min_by_key(|node| graph.neighbors(**node).count())
+        {
+            Some(min) => *min,
+            None => return 0,
+        };
+        let neighbors: Vec<NodeId> = graph.neighbors(min).collect();
+        nodes
+            .iter()
+            .filter(|node| **node != min)
+            .map(|node| (min, *node))
+            .chain(
+                neighbors
+                    .iter()
+                    .flat_map(|a| neighbors.iter().map(move |b| (*a, *b)))
+                    .filter(|(a, b)| a < b),
+            )
+            .collect()
+    };
+
+    pairs
+        .into_iter()
+        .filter(|(a, b)| !adjacent(*a, *b))
+        .map(|(a, b)| local(a, b))
+        .min()
+        .unwrap_or_else(|| nodes.len().saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+
+    #[test]
+    fn max_flow_sums_paths() {
+        let mut graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 3);
+        graph.add_edge(0, 2, 2);
+        graph.add_edge(1, 2, 5);
+        graph.add_edge(1, 3, 2);
+        graph.add_edge(2, 3, 3);
+        assert_eq!(max_flow(&graph, 0, 3), 5);
+        assert_eq!(max_flow(&graph, 3, 0), 0);
+    }
+
+    #[test]
+    fn stoer_wagner() {
+        let (weight, side) = stoer_wagner_min_cut(&generators::complete(4));
+        assert_eq!((weight, side.len()), (3, 1));
+
+        let mut barbell = generators::complete(4);
+        for _ in 0..4 {
+            barbell.add_node(());
+        }
+        for a in 4..8 {
+            for b in a + 1..8 {
+                barbell.add_edge(a, b, 1);
+            }
+        }
+        assert_eq!(stoer_wagner_min_cut(&barbell), (0, vec![4, 5, 6, 7]));
+        barbell.add_edge(3, 4, 2);
+        assert_eq!(stoer_wagner_min_cut(&barbell), (2, vec![4, 5, 6, 7]));
+    }
+
+    #[test]
+    fn connectivity_of_families() {
+        let petersen = generators::petersen();
+        assert_eq!(edge_connectivity(&petersen), 3);
+        assert_eq!(node_connectivity(&petersen), 3);
+        assert_eq!(node_connectivity(&generators::complete(5)), 4);
+        assert_eq!(node_connectivity(&generators::star(5)), 1);
+        assert_eq!(edge_connectivity(&generators::path(4)), 1);
+        assert_eq!(node_connectivity(&generators::hypercube(3)), 3);
+    }
+
+    #[test]
+    fn directed_connectivity() {
+        let mut graph = Graph::new();
+        for _ in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        assert_eq!(edge_connectivity(&graph), 0);
+        graph.add_edge(2, 0, 1);
+        assert_eq!(edge_connectivity(&graph), 1);
+        assert_eq!(node_connectivity(&graph), 1);
+    }
+
+    #[test]
+    fn hidden_nodes() {
+        let graph = generators::complete(3);
+        let edge = graph.filter_nodes(|node| node.id() != 2);
+        assert_eq!(node_connectivity(&edge), 1);
+        assert_eq!(edge_connectivity(&edge), 1);
+        assert_eq!(stoer_wagner_min_cut(&edge), (1, vec![1]));
+
+        let graph = generators::complete(5);
+        let k4 = graph.filter_nodes(|node| node.id() != 0);
+        assert_eq!(node_connectivity(&k4), 3);
+        assert_eq!(edge_connectivity(&k4), 3);
+
+        let path = generators::path(3);
+        let split = path.filter_nodes(|node| node.id() != 1);
+        assert_eq!(node_connectivity(&split), 0);
+        assert_eq!(edge_connectivity(&split), 0);
+    }
+}
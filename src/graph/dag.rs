@@ -0,0 +1,372 @@
+use std::convert::TryFrom;
+
+use super::{algo::Cycle, EdgeId, Graph, GraphNode, NodeId};
+use crate::prelude::{EdgeRef, GraphBase, IntoEdges, IntoNeighbors, NodeCount, Visitable};
+
+/// A directed acyclic graph. Edges that would close a cycle are refused, and a
+/// topological order of the nodes is kept up to date as edges come in, using the
+/// algorithm of Pearce and Kelly: an edge that agrees with the current order is
+/// accepted at once, and otherwise only the nodes between its endpoints in the
+/// order are visited.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Dag, WouldCycle};
+/// let mut dag = Dag::new();
+/// let fetch = dag.add_node("fetch");
+/// let build = dag.add_node("build");
+/// let test = dag.add_node("test");
+/// dag.add_edge(test, build, 1).unwrap();
+/// dag.add_edge(build, fetch, 1).unwrap();
+///
+/// assert_eq!(dag.add_edge(fetch, test, 1), Err(WouldCycle));
+/// assert_eq!(dag.topological_order(), &[test, build, fetch]);
+/// assert_eq!(dag.descendants(test).collect::<Vec<_>>(), vec![build, fetch]);
+/// ```
+pub struct Dag<T = ()> {
+    graph: Graph<T>,
+    order: Vec<NodeId>,
+    position: Vec<usize>,
+}
+
+/// Returned by `Dag::add_edge` when the edge would close a cycle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WouldCycle;
+
+impl<T> Default for Dag<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Dag<T> {
+    /// Creates an empty DAG
+    pub fn new() -> Self {
+        Self {
+            graph: Graph::new(),
+            order: vec![],
+            position: vec![],
+        }
+    }
+
+    /// Adds a node holding `value` and returns its id. New nodes come last in the
+    /// topological order.
+    pub fn add_node(&mut self, value: T) -> NodeId {
+        let id = self.graph.add_node(value);
+        self.order.push(id);
+        self.position.push(id);
+        id
+    }
+
+    /// Adds an edge from `from` to `to` and returns its id, or fails if `to`
+    /// already reaches `from`. Self loops always fail.
+    ///
+    /// Panics if any of the two nodes does not exist.
+    pub fn add_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        weight: usize,
+    ) -> Result<EdgeId, WouldCycle> {
+        assert!(
+            from < self.node_count() && to < self.node_count(),
+            "Cannot add an edge between nodes that do not exist"
+        );
+        if from == to {
+            return Err(WouldCycle);
+        }
+
+        let (lower, upper) = (self.position[to], self.position[from]);
+        if lower < upper {
+            // Nodes after `from` in the order cannot reach it, so the search
+            // forward from `to` stays within `lower..upper`
+            let forward = self.collect(to, upper, false).ok_or(WouldCycle)?;
+            let backward = self
+                .collect(from, lower, true)
+                .expect("Nodes before `to` cannot be reached from it");
+            self.reorder(backward, forward);
+        }
+
+        Ok(self.graph.add_edge(from, to, weight))
+    }
+
+    /// The nodes reached from `start` (following edges backwards if `backwards`)
+    /// without leaving the order positions up to `bound` (or from `bound`, when
+    /// going backwards). Returns `None` if the node at `bound` is reached.
+    fn collect(&self, start: NodeId, bound: usize, backwards: bool) -> Option<Vec<NodeId>> {
+        let mut visited = vec![start];
+        let mut seen = vec![false; self.node_count()];
+        seen[start] = true;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for next in self.adjacent(node, backwards) {
+                let position = self.position[next];
+                if position == bound {
+                    return None;
+                }
+                let inside = if backwards {
+                    position > bound
+                } else {
+                    position < bound
+                };
+                if inside && !seen[next] {
+                    seen[next] = true;
+                    visited.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        Some(visited)
+    }
+
+    /// Moves the `backward` nodes before the `forward` ones, reusing the order
+    /// positions they held between them
+    fn reorder(&mut self, mut backward: Vec<NodeId>, mut forward: Vec<NodeId>) {
+        backward.sort_unstable_by_key(|n| self.position[*n]);
+        forward.sort_unstable_by_key(|n| self.position[*n]);
+        let mut positions: Vec<usize> = backward
+            .iter()
+            .chain(&forward)
+            .map(|n| self.position[*n])
+            .collect();
+        positions.sort_unstable();
+
+        for (node, position) in backward.into_iter().chain(forward).zip(positions) {
+            self.position[node] = position;
+            self.order[position] = node;
+        }
+    }
+
+    fn adjacent(&self, node: NodeId, backwards: bool) -> impl Iterator<Item = NodeId> + '_ {
+        let edges = if backwards {
+            &self.graph.nodes[node].incoming
+        } else {
+            &self.graph.nodes[node].edges
+        };
+        edges.iter().map(move |e| self.graph.edges[*e].other(node))
+    }
+
+    /// The nodes sorted so that every edge goes from an earlier node to a later one
+    pub fn topological_order(&self) -> &[NodeId] {
+        &self.order
+    }
+
+    /// The number of nodes in the DAG
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// The number of edges in the DAG
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Returns the node with id `id`, if any
+    pub fn node(&self, id: NodeId) -> Option<&GraphNode<T>> {
+        self.graph.node(id)
+    }
+
+    /// The nodes with an edge to `node`, once for each edge
+    pub fn parents(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.adjacent(node, true)
+    }
+
+    /// The nodes `node` has an edge to, once for each edge
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.adjacent(node, false)
+    }
+
+    /// Walks the nodes that reach `node`, nearest first
+    pub fn ancestors(&self, node: NodeId) -> DagWalker<'_, T> {
+        DagWalker::new(self, node, true)
+    }
+
+    /// Walks the nodes reached from `node`, nearest first
+    pub fn descendants(&self, node: NodeId) -> DagWalker<'_, T> {
+        DagWalker::new(self, node, false)
+    }
+
+    /// The underlying graph
+    pub fn graph(&self) -> &Graph<T> {
+        &self.graph
+    }
+
+    /// Unwraps the underlying graph
+    pub fn into_graph(self) -> Graph<T> {
+        self.graph
+    }
+}
+
+impl<T> TryFrom<Graph<T>> for Dag<T> {
+    type Error = Cycle;
+
+    /// Wraps a directed graph, failing if it has a cycle
+    ///
+    /// Panics if the graph is undirected.
+    fn try_from(graph: Graph<T>) -> Result<Self, Cycle> {
+        assert!(graph.is_directed(), "A DAG needs a directed graph");
+        let order = super::algo::toposort(&graph)?;
+        let mut position = vec![0; order.len()];
+        for (i, &node) in order.iter().enumerate() {
+            position[node] = i;
+        }
+
+        Ok(Self {
+            graph,
+            order,
+            position,
+        })
+    }
+}
+
+/// A breadth first iterator over the ancestors or the descendants of a node in
+/// a `Dag`, not including the node itself
+pub struct DagWalker<'a, T> {
+    dag: &'a Dag<T>,
+    queue: std::collections::VecDeque<NodeId>,
+    visited: Vec<bool>,
+    backwards: bool,
+}
+
+impl<'a, T> DagWalker<'a, T> {
+    fn new(dag: &'a Dag<T>, start: NodeId, backwards: bool) -> Self {
+        let mut walker = Self {
+            dag,
+            queue: Default::default(),
+            visited: vec![false; dag.node_count()],
+            backwards,
+        };
+        walker.visit_from(start);
+        walker
+    }
+
+    fn visit_from(&mut self, node: NodeId) {
+        for next in self.dag.adjacent(node, self.backwards) {
+            if !std::mem::replace(&mut self.visited[next], true) {
+                self.queue.push_back(next);
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for DagWalker<'a, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.queue.pop_front()?;
+        self.visit_from(node);
+        Some(node)
+    }
+}
+
+impl<T> GraphBase for Dag<T> {
+    fn is_directed(&self) -> bool {
+        true
+    }
+}
+
+impl<T> NodeCount for Dag<T> {
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+impl<T> IntoNeighbors for Dag<T> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        IntoNeighbors::neighbors(&self.graph, node)
+    }
+}
+
+impl<T> IntoEdges for Dag<T> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        IntoEdges::edges(&self.graph, node)
+    }
+}
+
+impl<T> Visitable for Dag<T> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        self.graph.visit_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::toposort;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn is_topological(dag: &Dag) -> bool {
+        dag.graph()
+            .edges()
+            .all(|e| dag.position[e.from()] < dag.position[e.to()])
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let mut dag = Dag::new();
+        for _ in 0..4 {
+            dag.add_node(());
+        }
+        dag.add_edge(3, 2, 1).unwrap();
+        dag.add_edge(2, 1, 1).unwrap();
+        dag.add_edge(1, 0, 1).unwrap();
+        assert_eq!(dag.add_edge(0, 3, 1), Err(WouldCycle));
+        assert_eq!(dag.add_edge(1, 1, 1), Err(WouldCycle));
+        assert_eq!(dag.topological_order(), &[3, 2, 1, 0]);
+        assert_eq!(dag.edge_count(), 3);
+    }
+
+    #[test]
+    fn random_edges_keep_the_order() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut dag = Dag::new();
+        for _ in 0..30 {
+            dag.add_node(());
+        }
+        for _ in 0..300 {
+            let (a, b) = (rng.gen_range(0..30), rng.gen_range(0..30));
+            let cycles = a == b || dag.descendants(b).any(|n| n == a);
+            assert_eq!(dag.add_edge(a, b, 1).is_err(), cycles);
+            assert!(is_topological(&dag));
+        }
+        assert!(toposort(dag.graph()).is_ok());
+    }
+
+    #[test]
+    fn walkers() {
+        let mut dag = Dag::new();
+        for _ in 0..5 {
+            dag.add_node(());
+        }
+        dag.add_edge(0, 1, 1).unwrap();
+        dag.add_edge(0, 2, 1).unwrap();
+        dag.add_edge(1, 3, 1).unwrap();
+        dag.add_edge(2, 3, 1).unwrap();
+        dag.add_edge(3, 4, 1).unwrap();
+
+        assert_eq!(dag.parents(3).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(dag.children(0).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(dag.ancestors(4).collect::<Vec<_>>(), vec![3, 1, 2, 0]);
+        assert_eq!(dag.descendants(1).collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn from_graph() {
+        let mut graph = Graph::new();
+        for _ in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(2, 0, 1);
+        graph.add_edge(0, 1, 1);
+        let mut dag = Dag::try_from(graph).unwrap();
+        assert_eq!(dag.topological_order(), &[2, 0, 1]);
+        assert_eq!(dag.add_edge(1, 2, 1), Err(WouldCycle));
+
+        let mut graph = dag.into_graph();
+        graph.add_edge(1, 2, 1);
+        assert!(Dag::try_from(graph).is_err());
+    }
+}
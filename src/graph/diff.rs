@@ -0,0 +1,378 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Graph;
+
+/// The changes turning one graph into another, as found by `diff`. Nodes are
+/// named by their key, so a patch does not depend on node ids and can be applied
+/// to any graph with the same keys.
+///
+/// With the `serde` feature enabled, patches can be serialized, for example to
+/// keep an audit log of the changes made to a graph.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphPatch<K, T> {
+    /// Nodes only found in the new graph, with their values
+    pub added_nodes: Vec<(K, T)>,
+    /// Nodes only found in the old graph
+    pub removed_nodes: Vec<K>,
+    /// Nodes whose value changed, with their new value
+    pub modified_nodes: Vec<(K, T)>,
+    /// Edges only found in the new graph
+    pub added_edges: Vec<PatchEdge<K>>,
+    /// Edges only found in the old graph
+    pub removed_edges: Vec<PatchEdge<K>>,
+    /// Edges whose weight changed, as found in the old graph and with their new
+    /// weight
+    pub modified_edges: Vec<(PatchEdge<K>, usize)>,
+}
+
+/// An edge of a `GraphPatch`, going between the nodes with keys `from` and `to`.
+/// On undirected graphs `from` is the smaller key.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatchEdge<K> {
+    pub from: K,
+    pub to: K,
+    pub weight: usize,
+}
+
+impl<K, T> GraphPatch<K, T> {
+    /// Returns `true` if the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.modified_edges.is_empty()
+    }
+}
+
+/// A value for each edge between two keys, by default its weight, in edge id
+/// order
+type EdgeMap<K, V = usize> = BTreeMap<(K, K), Vec<V>>;
+
+/// Maps every node to its key
+///
+/// Panics if two nodes share a key.
+fn keys<T, K: Ord + Clone, F: Fn(&T) -> K>(graph: &Graph<T>, key: &F) -> Vec<K> {
+    let keys: Vec<K> = graph.nodes.iter().map(|n| key(&n.value)).collect();
+    let mut sorted: Vec<&K> = keys.iter().collect();
+    sorted.sort_unstable();
+    assert!(
+        sorted.windows(2).all(|w| w[0] != w[1]),
+        "Cannot diff a graph where two nodes share a key"
+    );
+    keys
+}
+
+/// The two keys of an edge, smallest first on undirected graphs
+fn endpoints<T, K: Ord + Clone>(graph: &Graph<T>, keys: &[K], from: usize, to: usize) -> (K, K) {
+    let (from, to) = (keys[from].clone(), keys[to].clone());
+    if !graph.directed && to < from {
+        (to, from)
+    } else {
+        (from, to)
+    }
+}
+
+fn edge_map<T, K: Ord + Clone>(graph: &Graph<T>, keys: &[K]) -> EdgeMap<K> {
+    let mut map = EdgeMap::new();
+    for edge in &graph.edges {
+        map.entry(endpoints(graph, keys, edge.from, edge.to))
+            .or_insert_with(Vec::new)
+            .push(edge.weight);
+    }
+    map
+}
+
+/// Compares two graphs, matching their nodes by the key `key` computes from
+/// their values. Parallel edges are matched by weight first, and the remaining
+/// ones are reported as modified in id order.
+///
+/// Panics if one graph is directed and the other is not, or if two nodes of a
+/// graph share a key.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{diff, Graph};
+/// let mut old = Graph::new();
+/// let a = old.add_node(("a", 1));
+/// let b = old.add_node(("b", 1));
+/// old.add_edge(a, b, 10);
+///
+/// let mut new = Graph::new();
+/// let a = new.add_node(("a", 2));
+/// let b = new.add_node(("b", 1));
+/// let c = new.add_node(("c", 1));
+/// new.add_edge(a, b, 10);
+/// new.add_edge(b, c, 5);
+///
+/// let patch = diff(&old, &new, |(name, _)| *name);
+/// assert_eq!(patch.added_nodes, vec![("c", ("c", 1))]);
+/// assert_eq!(patch.modified_nodes, vec![("a", ("a", 2))]);
+/// assert_eq!(patch.added_edges.len(), 1);
+/// assert_eq!(old.apply(&patch, |(name, _)| *name).node_count(), 3);
+/// ```
+pub fn diff<T, K, F>(old: &Graph<T>, new: &Graph<T>, key: F) -> GraphPatch<K, T>
+where
+    T: Clone + PartialEq,
+    K: Ord + Clone,
+    F: Fn(&T) -> K,
+{
+    assert_eq!(
+        old.directed, new.directed,
+        "Cannot diff a directed graph with an undirected one"
+    );
+    let old_keys = keys(old, &key);
+    let new_keys = keys(new, &key);
+    let mut old_nodes: BTreeMap<&K, &T> = old_keys
+        .iter()
+        .zip(&old.nodes)
+        .map(|(k, n)| (k, &n.value))
+        .collect();
+
+    let mut patch = GraphPatch {
+        added_nodes: vec![],
+        removed_nodes: vec![],
+        modified_nodes: vec![],
+        added_edges: vec![],
+        removed_edges: vec![],
+        modified_edges: vec![],
+    };
+    for (k, node) in new_keys.iter().zip(&new.nodes) {
+        match old_nodes.remove(k) {
+            None => patch.added_nodes.push((k.clone(), node.value.clone())),
+            Some(value) if *value != node.value => {
+                patch.modified_nodes.push((k.clone(), node.value.clone()))
+            }
+            Some(_) => {}
+        }
+    }
+    patch.removed_nodes = old_nodes.into_keys().cloned().collect();
+
+    let mut old_edges = edge_map(old, &old_keys);
+    let mut new_edges = edge_map(new, &new_keys);
+    let pairs: BTreeSet<(K, K)> = old_edges.keys().chain(new_edges.keys()).cloned().collect();
+    for (from, to) in pairs {
+        let mut removed = old_edges
+            .remove(&(from.clone(), to.clone()))
+            .unwrap_or_default();
+        let mut added = new_edges
+            .remove(&(from.clone(), to.clone()))
+            .unwrap_or_default();
+        // Edges with the same weight on both sides are left alone
+        removed.retain(|w| match added.iter().position(|a| a == w) {
+            Some(i) => {
+                added.remove(i);
+                false
+            }
+            None => true,
+        });
+
+        let edge = |weight| PatchEdge {
+            from: from.clone(),
+            to: to.clone(),
+            weight,
+        };
+        let modified = removed.len().min(added.len());
+        for (&old_weight, &new_weight) in removed.iter().zip(&added) {
+            patch.modified_edges.push((edge(old_weight), new_weight));
+        }
+        patch
+            .removed_edges
+            .extend(removed[modified..].iter().map(|w| edge(*w)));
+        patch
+            .added_edges
+            .extend(added[modified..].iter().map(|w| edge(*w)));
+    }
+
+    patch
+}
+
+impl<T: Clone> Graph<T> {
+    /// Builds the graph obtained by applying `patch` to this one, with nodes
+    /// identified by the same key `diff` used. Nodes kept from this graph come
+    /// first, in their current order, followed by the added nodes in the order
+    /// they had in the new graph.
+    ///
+    /// Panics if the patch does not fit this graph: if it adds a node that
+    /// exists, or removes or changes a node or edge that does not.
+    pub fn apply<K, F>(&self, patch: &GraphPatch<K, T>, key: F) -> Graph<T>
+    where
+        K: Ord + Clone,
+        F: Fn(&T) -> K,
+    {
+        let keys = keys(self, &key);
+        let removed: BTreeSet<&K> = patch.removed_nodes.iter().collect();
+        let modified: BTreeMap<&K, &T> = patch.modified_nodes.iter().map(|(k, v)| (k, v)).collect();
+
+        let mut graph = self.empty();
+        let mut ids = BTreeMap::new();
+        for (k, node) in keys.iter().zip(&self.nodes) {
+            if removed.contains(k) {
+                continue;
+            }
+            let value = modified.get(k).map_or(&node.value, |v| *v);
+            ids.insert(k.clone(), graph.add_node(value.clone()));
+        }
+        assert_eq!(
+            ids.len() + removed.len(),
+            self.node_count(),
+            "The patch removes nodes that do not exist"
+        );
+        assert!(
+            modified.keys().all(|k| ids.contains_key(*k)),
+            "The patch changes nodes that do not exist"
+        );
+        for (k, value) in &patch.added_nodes {
+            let id = graph.add_node(value.clone());
+            assert!(
+                ids.insert(k.clone(), id).is_none(),
+                "The patch adds nodes that already exist"
+            );
+        }
+
+        // Changes still waiting for a matching edge, by endpoints and weight
+        let mut changes: EdgeMap<K, (usize, Option<usize>)> = BTreeMap::new();
+        for edge in &patch.removed_edges {
+            let pair = (edge.from.clone(), edge.to.clone());
+            changes.entry(pair).or_default().push((edge.weight, None));
+        }
+        for (edge, weight) in &patch.modified_edges {
+            let pair = (edge.from.clone(), edge.to.clone());
+            changes
+                .entry(pair)
+                .or_default()
+                .push((edge.weight, Some(*weight)));
+        }
+
+        for edge in &self.edges {
+            let pair = endpoints(self, &keys, edge.from, edge.to);
+            let change = changes.get_mut(&pair).and_then(|c| {
+                let i = c.iter().position(|(w, _)| *w == edge.weight)?;
+                Some(c.remove(i).1)
+            });
+            let weight = match change {
+                Some(None) => continue,
+                Some(Some(weight)) => weight,
+                None => edge.weight,
+            };
+            graph.add_edge(ids[&pair.0], ids[&pair.1], weight);
+        }
+        assert!(
+            changes.values().all(Vec::is_empty),
+            "The patch changes edges that do not exist"
+        );
+        for edge in &patch.added_edges {
+            graph.add_edge(ids[&edge.from], ids[&edge.to], edge.weight);
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Node;
+
+    type Versioned = Graph<(char, u32)>;
+
+    fn make_test_graphs() -> (Versioned, Versioned) {
+        let mut old = Graph::new();
+        for (name, version) in [('a', 1), ('b', 1), ('c', 1), ('d', 1)] {
+            old.add_node((name, version));
+        }
+        old.add_edge(0, 1, 1);
+        old.add_edge(0, 1, 2);
+        old.add_edge(1, 2, 3);
+        old.add_edge(2, 3, 4);
+
+        let mut new = Graph::new();
+        for (name, version) in [('c', 1), ('b', 2), ('a', 1), ('e', 1)] {
+            new.add_node((name, version));
+        }
+        new.add_edge(2, 1, 1);
+        new.add_edge(2, 1, 5);
+        new.add_edge(0, 1, 3);
+        new.add_edge(0, 3, 6);
+        (old, new)
+    }
+
+    fn edges(graph: &Versioned) -> Vec<(char, char, usize)> {
+        let name = |id| graph.node(id).unwrap().value().0;
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|e| (name(e.from()), name(e.to()), e.weight()))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn finds_changes() {
+        let (old, new) = make_test_graphs();
+        let patch = diff(&old, &new, |n| n.0);
+        assert_eq!(patch.added_nodes, vec![('e', ('e', 1))]);
+        assert_eq!(patch.removed_nodes, vec!['d']);
+        assert_eq!(patch.modified_nodes, vec![('b', ('b', 2))]);
+
+        let edge = |from, to, weight| PatchEdge { from, to, weight };
+        assert_eq!(
+            patch.added_edges,
+            vec![edge('c', 'b', 3), edge('c', 'e', 6)]
+        );
+        assert_eq!(
+            patch.removed_edges,
+            vec![edge('b', 'c', 3), edge('c', 'd', 4)]
+        );
+        assert_eq!(patch.modified_edges, vec![(edge('a', 'b', 2), 5)]);
+        assert!(diff(&new, &new, |n| n.0).is_empty());
+    }
+
+    #[test]
+    fn apply_reproduces_the_new_graph() {
+        let (old, new) = make_test_graphs();
+        let patch = diff(&old, &new, |n| n.0);
+        let patched = old.apply(&patch, |n| n.0);
+
+        let mut values: Vec<_> = patched.nodes().map(|n| *n.value()).collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![('a', 1), ('b', 2), ('c', 1), ('e', 1)]);
+        assert_eq!(edges(&patched), edges(&new));
+    }
+
+    #[test]
+    fn undirected_edges_match_either_way() {
+        let mut old = Graph::new_undirected();
+        let a = old.add_node('a');
+        let b = old.add_node('b');
+        old.add_edge(a, b, 1);
+
+        let mut new = Graph::new_undirected();
+        let b = new.add_node('b');
+        let a = new.add_node('a');
+        new.add_edge(b, a, 2);
+
+        let patch = diff(&old, &new, |n| *n);
+        let edge = PatchEdge {
+            from: 'a',
+            to: 'b',
+            weight: 1,
+        };
+        assert_eq!(patch.modified_edges, vec![(edge, 2)]);
+        let patched = old.apply(&patch, |n| *n);
+        assert_eq!(patched.edge(0).unwrap().weight(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let (old, new) = make_test_graphs();
+        let patch = diff(&old, &new, |n| n.0);
+        let json = serde_json::to_string(&patch).unwrap();
+        let patch: GraphPatch<char, (char, u32)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(old.apply(&patch, |n| n.0).edge_count(), 4);
+    }
+}
@@ -0,0 +1,274 @@
+//! Dominator analysis for control flow graphs. A node `a` dominates `b` when
+//! every path from the root to `b` goes through `a`.
+//!
+//! Post-dominators are the dominators of the reversed graph, rooted at the exit
+//! node: `dominators(&graph.reversed(), exit)`.
+
+use super::Graph;
+use crate::prelude::{IntoNeighbors, NodeCount, NodeId};
+
+/// The dominance relation of the nodes reachable from a root
+#[derive(Debug, Clone)]
+pub struct Dominators {
+    root: NodeId,
+    idoms: Vec<Option<NodeId>>,
+    frontiers: Vec<Vec<NodeId>>,
+}
+
+impl Dominators {
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// The closest strict dominator of `node`. `None` for the root and for nodes
+    /// that cannot be reached from it.
+    pub fn immediate_dominator(&self, node: NodeId) -> Option<NodeId> {
+        if node == self.root {
+            None
+        } else {
+            self.idoms[node]
+        }
+    }
+
+    /// Every dominator of `node`, starting from `node` itself and ending with the
+    /// root. `None` if the node cannot be reached from the root.
+    pub fn dominators(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.idoms[node]?;
+        let mut chain = vec![node];
+        let mut current = node;
+        while let Some(next) = self.immediate_dominator(current) {
+            chain.push(next);
+            current = next;
+        }
+
+        Some(chain)
+    }
+
+    /// Returns `true` if every path from the root to `b` goes through `a`
+    pub fn dominates(&self, a: NodeId, b: NodeId) -> bool {
+        self.dominators(b).is_some_and(|chain| chain.contains(&a))
+    }
+
+    /// The nodes immediately dominated by `node`
+    pub fn children(&self, node: NodeId) -> Vec<NodeId> {
+        (0..self.idoms.len())
+            .filter(|n| self.immediate_dominator(*n) == Some(node))
+            .collect()
+    }
+
+    /// The dominance frontier of `node`: the nodes where its dominance ends, as
+    /// they can also be reached without going through it
+    pub fn dominance_frontier(&self, node: NodeId) -> &[NodeId] {
+        &self.frontiers[node]
+    }
+
+    /// Builds the dominator tree, with an edge from every immediate dominator to
+    /// the nodes it dominates. Node ids are the ones of the analysed graph.
+    pub fn tree(&self) -> Graph {
+        let mut tree = Graph::new();
+        for _ in 0..self.idoms.len() {
+            tree.add_node(());
+        }
+        for node in 0..self.idoms.len() {
+            if let Some(idom) = self.immediate_dominator(node) {
+                tree.add_edge(idom, node, 1);
+            }
+        }
+
+        tree
+    }
+}
+
+/// Computes the dominators of the nodes reachable from `root` with the
+/// Cooper–Harvey–Kennedy iterative algorithm
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, dominators::dominators};
+/// // 0 -> 1 -> 3, 0 -> 2 -> 3
+/// let mut graph = Graph::new();
+/// for _ in 0..4 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 1);
+/// graph.add_edge(0, 2, 1);
+/// graph.add_edge(1, 3, 1);
+/// graph.add_edge(2, 3, 1);
+///
+/// let dominators = dominators(&graph, 0);
+/// assert_eq!(dominators.immediate_dominator(3), Some(0));
+/// assert_eq!(dominators.dominance_frontier(1), &[3]);
+/// ```
+pub fn dominators<G: IntoNeighbors + NodeCount>(graph: &G, root: NodeId) -> Dominators {
+    let n = graph.node_count();
+    let postorder = postorder(graph, root);
+    let mut rank = vec![usize::MAX; n];
+    for (i, node) in postorder.iter().enumerate() {
+        rank[*node] = i;
+    }
+
+    let mut predecessors = vec![vec![]; n];
+    for node in postorder.iter().copied() {
+        for next in graph.neighbors(node) {
+            predecessors[next].push(node);
+        }
+    }
+
+    let mut idoms = vec![None; n];
+    idoms[root] = Some(root);
+    let intersect = |idoms: &[Option<NodeId>], mut a: NodeId, mut b: NodeId| {
+        while a != b {
+            while rank[a] < rank[b] {
+                a = idoms[a].unwrap();
+            }
+            while rank[b] < rank[a] {
+                b = idoms[b].unwrap();
+            }
+        }
+        a
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &node in postorder.iter().rev().skip(1) {
+            let mut processed = predecessors[node].iter().filter(|p| idoms[**p].is_some());
+            let first = *processed.next().unwrap();
+            let idom = processed.fold(first, |idom, p| intersect(&idoms, *p, idom));
+            if idoms[node] != Some(idom) {
+                idoms[node] = Some(idom);
+                changed = true;
+            }
+        }
+    }
+
+    let mut frontiers = vec![vec![]; n];
+    for &node in &postorder {
+        // Nodes with a single predecessor are strictly dominated by it, except
+        // for the root which has no strict dominator at all
+        if predecessors[node].len() < 2 && node != root {
+            continue;
+        }
+        let stop = if node == root { None } else { idoms[node] };
+        for &p in &predecessors[node] {
+            let mut runner = p;
+            while Some(runner) != stop {
+                if !frontiers[runner].contains(&node) {
+                    frontiers[runner].push(node);
+                }
+                if runner == root {
+                    break;
+                }
+                runner = idoms[runner].unwrap();
+            }
+        }
+    }
+    frontiers.iter_mut().for_each(|f| f.sort_unstable());
+
+    Dominators {
+        root,
+        idoms,
+        frontiers,
+    }
+}
+
+fn postorder<G: IntoNeighbors + NodeCount>(graph: &G, root: NodeId) -> Vec<NodeId> {
+    let mut visited = vec![false; graph.node_count()];
+    let mut order = vec![];
+    let mut stack = vec![(root, graph.neighbors(root))];
+    visited[root] = true;
+    while let Some((node, neighbors)) = stack.last_mut() {
+        match neighbors.find(|n| !visited[*n]) {
+            Some(next) => {
+                visited[next] = true;
+                stack.push((next, graph.neighbors(next)));
+            }
+            None => {
+                order.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The control flow graph of a loop with a branch inside:
+    /// 0 -> 1 -> 2 -> 4 -> 1, 1 -> 3 -> 4, 1 -> 5, plus an unreachable node 6
+    fn make_test_graph() -> Graph {
+        let mut graph = Graph::new();
+        for _ in 0..7 {
+            graph.add_node(());
+        }
+        for (a, b) in [
+            (0, 1),
+            (1, 2),
+            (1, 3),
+            (2, 4),
+            (3, 4),
+            (4, 1),
+            (1, 5),
+            (6, 4),
+        ]
+        .iter()
+        {
+            graph.add_edge(*a, *b, 1);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn immediate_dominators() {
+        let graph = make_test_graph();
+        let dominators = dominators(&graph, 0);
+        let idoms: Vec<_> = (0..7).map(|n| dominators.immediate_dominator(n)).collect();
+        assert_eq!(
+            idoms,
+            vec![None, Some(0), Some(1), Some(1), Some(1), Some(1), None]
+        );
+        assert_eq!(dominators.dominators(4), Some(vec![4, 1, 0]));
+        assert_eq!(dominators.dominators(6), None);
+        assert!(dominators.dominates(1, 5));
+        assert!(!dominators.dominates(2, 4));
+        assert_eq!(dominators.children(1), vec![2, 3, 4, 5]);
+        assert_eq!(dominators.tree().edge_count(), 5);
+    }
+
+    #[test]
+    fn frontiers() {
+        let graph = make_test_graph();
+        let dominators = dominators(&graph, 0);
+        assert_eq!(dominators.dominance_frontier(2), &[4]);
+        assert_eq!(dominators.dominance_frontier(4), &[1]);
+        assert_eq!(dominators.dominance_frontier(1), &[1]);
+        assert!(dominators.dominance_frontier(0).is_empty());
+
+        let mut graph = graph;
+        graph.add_edge(5, 0, 1);
+        let dominators = super::dominators(&graph, 0);
+        assert_eq!(dominators.dominance_frontier(5), &[0]);
+        assert_eq!(dominators.dominance_frontier(0), &[0]);
+    }
+
+    #[test]
+    fn post_dominators() {
+        // 0 -> 1 -> 3, 0 -> 2 -> 3
+        let mut graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (2, 3)].iter() {
+            graph.add_edge(*a, *b, 1);
+        }
+
+        let post = dominators(&graph.reversed(), 3);
+        assert_eq!(post.immediate_dominator(0), Some(3));
+        assert_eq!(post.immediate_dominator(1), Some(3));
+        assert_eq!(post.dominance_frontier(1), &[0]);
+    }
+}
@@ -0,0 +1,362 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use super::{EdgeId, Graph, NodeId};
+use crate::prelude::{IntoNeighbors, NodeCount};
+
+/// Shortest distances from a fixed source, kept up to date while the graph
+/// grows. Adding an edge or lowering a weight can only shorten paths, so only
+/// the nodes that get closer are visited again, running Dijkstra's algorithm
+/// from the endpoints of the changed edge.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{DynamicShortestPaths, Graph};
+/// let mut graph = Graph::new();
+/// for _ in 0..3 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 5);
+/// let bc = graph.add_edge(1, 2, 5);
+///
+/// let mut paths = DynamicShortestPaths::new(graph, 0);
+/// assert_eq!(paths.distance(2), Some(10));
+/// paths.add_edge(0, 2, 7);
+/// assert_eq!(paths.distance(2), Some(7));
+/// paths.decrease_weight(bc, 1);
+/// assert_eq!(paths.distance(2), Some(6));
+/// assert_eq!(paths.path(2), Some(vec![0, 1, 2]));
+/// ```
+pub struct DynamicShortestPaths<T = ()> {
+    graph: Graph<T>,
+    source: NodeId,
+    distances: Vec<Option<usize>>,
+    parents: Vec<Option<NodeId>>,
+}
+
+impl<T> DynamicShortestPaths<T> {
+    /// Computes the distances from `source` to every node of `graph`
+    ///
+    /// Panics if `source` does not exist.
+    pub fn new(graph: Graph<T>, source: NodeId) -> Self {
+        assert!(
+            source < graph.node_count(),
+            "The source must be a node of the graph"
+        );
+        let n = graph.node_count();
+        let mut paths = Self {
+            graph,
+            source,
+            distances: vec![None; n],
+            parents: vec![None; n],
+        };
+        paths.distances[source] = Some(0);
+        paths.repair(vec![Reverse((0, source))]);
+        paths
+    }
+
+    /// Adds a node holding `value`, unreachable until edges lead to it
+    pub fn add_node(&mut self, value: T) -> NodeId {
+        self.distances.push(None);
+        self.parents.push(None);
+        self.graph.add_node(value)
+    }
+
+    /// Adds an edge to the graph and updates the distances it shortens
+    ///
+    /// Panics if any of the two nodes does not exist, or if the policy of the
+    /// graph forbids the edge.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: usize) -> EdgeId {
+        let id = self.graph.add_edge(from, to, weight);
+        self.relax(id);
+        id
+    }
+
+    /// Lowers the weight of edge `edge` to `weight` and updates the distances
+    /// this shortens. A weight of 0 is accepted.
+    ///
+    /// Panics if the edge does not exist or if `weight` is larger than its
+    /// current weight, since longer edges may need a full recomputation.
+    pub fn decrease_weight(&mut self, edge: EdgeId, weight: usize) {
+        let current = &mut self.graph.edges[edge];
+        assert!(
+            weight <= current.weight,
+            "Cannot increase the weight of an edge"
+        );
+        current.weight = weight;
+        self.relax(edge);
+    }
+
+    /// Repairs the distances after edge `edge` was added or made lighter
+    fn relax(&mut self, edge: EdgeId) {
+        let edge = self.graph.edges[edge];
+        let mut ends = vec![(edge.from, edge.to)];
+        if !self.graph.directed {
+            ends.push((edge.to, edge.from));
+        }
+
+        let mut heap = vec![];
+        for (from, to) in ends {
+            if let Some(distance) = self.distances[from] {
+                let distance = distance + edge.weight;
+                if self.distances[to].is_none_or(|d| distance < d) {
+                    self.distances[to] = Some(distance);
+                    self.parents[to] = Some(from);
+                    heap.push(Reverse((distance, to)));
+                }
+            }
+        }
+        self.repair(heap);
+    }
+
+    /// Dijkstra's algorithm, starting from the nodes in `heap` whose distance
+    /// just got shorter
+    fn repair(&mut self, heap: Vec<Reverse<(usize, NodeId)>>) {
+        let mut heap = BinaryHeap::from(heap);
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if self.distances[node].is_some_and(|d| cost > d) {
+                continue;
+            }
+
+            for edge in self.graph.edges_of(node) {
+                let (next, next_cost) = (edge.other(node), cost + edge.weight);
+                if self.distances[next].is_none_or(|d| next_cost < d) {
+                    self.distances[next] = Some(next_cost);
+                    self.parents[next] = Some(node);
+                    heap.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+    }
+
+    /// The node distances are measured from
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// The length of the shortest path from the source to `node`, or `None` if
+    /// there is no path
+    pub fn distance(&self, node: NodeId) -> Option<usize> {
+        self.distances[node]
+    }
+
+    /// The distances from the source to every node, as `algo::dijkstra` reports
+    /// them
+    pub fn distances(&self) -> &[Option<usize>] {
+        &self.distances
+    }
+
+    /// A shortest path from the source to `node`, if there is any
+    pub fn path(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distances[node]?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(parent) = self.parents[current] {
+            path.push(parent);
+            current = parent;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// The underlying graph
+    pub fn graph(&self) -> &Graph<T> {
+        &self.graph
+    }
+
+    /// Unwraps the underlying graph
+    pub fn into_graph(self) -> Graph<T> {
+        self.graph
+    }
+}
+
+/// Tells whether two nodes are connected while edges are being added. Adding an
+/// edge takes nearly constant amortized time, and queries take logarithmic time
+/// since they leave the structure untouched. Edge direction is ignored, as in
+/// `algo::connected_components`.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{generators, DynamicConnectivity};
+/// let mut connectivity = DynamicConnectivity::new(&generators::path(3));
+/// let d = connectivity.add_node();
+/// assert!(connectivity.connected(0, 2));
+/// assert!(!connectivity.connected(0, d));
+/// connectivity.add_edge(2, d);
+/// assert!(connectivity.connected(0, d));
+/// assert_eq!(connectivity.component_count(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DynamicConnectivity {
+    parents: Vec<NodeId>,
+    sizes: Vec<usize>,
+    components: usize,
+}
+
+impl DynamicConnectivity {
+    /// Starts from the nodes and edges of `graph`. Hidden nodes keep their id
+    /// but do not count as components.
+    pub fn new<G: IntoNeighbors + NodeCount>(graph: &G) -> Self {
+        let n = graph.node_count();
+        let mut connectivity = Self {
+            parents: (0..n).collect(),
+            sizes: vec![1; n],
+            components: graph.node_ids().count(),
+        };
+        for node in 0..n {
+            for next in graph.neighbors(node) {
+                connectivity.add_edge(node, next);
+            }
+        }
+
+        connectivity
+    }
+
+    /// Adds a node without edges and returns its id, which matches the id the
+    /// next node added to the graph gets
+    pub fn add_node(&mut self) -> NodeId {
+        self.parents.push(self.parents.len());
+        self.sizes.push(1);
+        self.components += 1;
+        self.parents.len() - 1
+    }
+
+    /// Records an edge between `a` and `b`
+    pub fn add_edge(&mut self, a: NodeId, b: NodeId) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.components -= 1;
+    }
+
+    /// Returns `true` if a path joins `a` and `b`
+    pub fn connected(&self, a: NodeId, b: NodeId) -> bool {
+        self.root(a) == self.root(b)
+    }
+
+    /// The number of connected components
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The number of nodes connected to `node`, itself included
+    pub fn component_size(&self, node: NodeId) -> usize {
+        self.sizes[self.root(node)]
+    }
+
+    /// The root of the tree holding `node`. Union by size keeps trees of
+    /// logarithmic height, so this does not need to compress paths.
+    fn root(&self, mut node: NodeId) -> NodeId {
+        while self.parents[node] != node {
+            node = self.parents[node];
+        }
+        node
+    }
+
+    /// Like `root`, halving the path to it on the way
+    fn find(&mut self, mut node: NodeId) -> NodeId {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::{connected_components, dijkstra};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn matches_full_recomputation() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for directed in [true, false] {
+            let mut graph = if directed {
+                Graph::new()
+            } else {
+                Graph::new_undirected()
+            };
+            for _ in 0..40 {
+                graph.add_node(());
+            }
+            let mut paths = DynamicShortestPaths::new(graph, 0);
+            for _ in 0..150 {
+                let (a, b) = (rng.gen_range(0..40), rng.gen_range(0..40));
+                let edge = paths.add_edge(a, b, rng.gen_range(1..20));
+                if rng.gen_bool(0.3) {
+                    let weight = paths.graph().edge(edge).unwrap().weight();
+                    paths.decrease_weight(edge, rng.gen_range(0..=weight));
+                }
+                assert_eq!(paths.distances(), &dijkstra(paths.graph(), 0)[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let mut graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        let mut paths = DynamicShortestPaths::new(graph, 0);
+        let d = paths.add_node(());
+        assert_eq!(d, 4);
+        assert_eq!(paths.path(3), None);
+        assert_eq!(paths.path(0), Some(vec![0]));
+
+        paths.add_edge(2, d, 1);
+        assert_eq!(paths.path(d), Some(vec![0, 1, 2, 4]));
+        paths.add_edge(0, d, 2);
+        assert_eq!(paths.path(d), Some(vec![0, 4]));
+    }
+
+    #[test]
+    #[should_panic(expected = "increase the weight")]
+    fn weights_cannot_increase() {
+        let mut graph = Graph::new();
+        graph.add_node(());
+        let edge = graph.add_edge(0, 0, 1);
+        DynamicShortestPaths::new(graph, 0).decrease_weight(edge, 2);
+    }
+
+    #[test]
+    fn connectivity_matches_components() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut graph = Graph::new();
+        let mut connectivity = DynamicConnectivity::default();
+        for _ in 0..50 {
+            assert_eq!(graph.add_node(()), connectivity.add_node());
+        }
+        for _ in 0..60 {
+            let (a, b) = (rng.gen_range(0..50), rng.gen_range(0..50));
+            graph.add_edge(a, b, 1);
+            connectivity.add_edge(a, b);
+
+            let labels = connected_components(&graph);
+            let count = labels.iter().max().map_or(0, |l| l + 1);
+            assert_eq!(connectivity.component_count(), count);
+            let (c, d) = (rng.gen_range(0..50), rng.gen_range(0..50));
+            assert_eq!(connectivity.connected(c, d), labels[c] == labels[d]);
+        }
+
+        let view = graph.filter_nodes(|node| node.id() % 2 == 0);
+        let labels = connected_components(&view);
+        let count = labels
+            .iter()
+            .filter(|l| **l != usize::MAX)
+            .max()
+            .map_or(0, |l| l + 1);
+        assert_eq!(DynamicConnectivity::new(&view).component_count(), count);
+    }
+}
@@ -0,0 +1,274 @@
+//! Builders for well known families of graphs. Every generated graph is undirected
+//! and has unit edge weights.
+//!
+//! Random generators take any `rand::Rng`, so passing a seeded one makes their
+//! output reproducible.
+//!
+//! ## Example
+//! ```
+//! # use fluffy_structs::generators;
+//! use rand::{rngs::StdRng, SeedableRng};
+//!
+//! let a = generators::gnp(20, 0.3, &mut StdRng::seed_from_u64(7));
+//! let b = generators::gnp(20, 0.3, &mut StdRng::seed_from_u64(7));
+//! assert_eq!(a.edge_count(), b.edge_count());
+//! ```
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use super::{Graph, NodeId};
+
+fn from_edges(n: usize, edges: impl IntoIterator<Item = (NodeId, NodeId)>) -> Graph {
+    let mut graph = Graph::new_undirected();
+    for _ in 0..n {
+        graph.add_node(());
+    }
+    for (a, b) in edges {
+        graph.add_edge(a, b, 1);
+    }
+
+    graph
+}
+
+/// A graph with `n` nodes, each one connected to every other
+pub fn complete(n: usize) -> Graph {
+    from_edges(n, (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))))
+}
+
+/// `n` nodes connected in a line
+pub fn path(n: usize) -> Graph {
+    from_edges(n, (1..n).map(|b| (b - 1, b)))
+}
+
+/// `n` nodes connected in a ring. With fewer than three nodes there is no ring
+/// to close, and this returns `path(n)`.
+pub fn cycle(n: usize) -> Graph {
+    let mut graph = path(n);
+    if n > 2 {
+        graph.add_edge(n - 1, 0, 1);
+    }
+
+    graph
+}
+
+/// `n` nodes, where node `0` is connected to all the others
+pub fn star(n: usize) -> Graph {
+    from_edges(n, (1..n).map(|b| (0, b)))
+}
+
+/// `n` nodes, where nodes `1..n` form a ring and node `0` is connected to all of
+/// them. With fewer than four nodes this returns `star(n)`.
+pub fn wheel(n: usize) -> Graph {
+    let mut graph = star(n);
+    if n > 3 {
+        for a in 1..n {
+            graph.add_edge(a, a % (n - 1) + 1, 1);
+        }
+    }
+
+    graph
+}
+
+/// A `rows` by `cols` lattice. Node `r * cols + c` sits at row `r` and column `c`.
+pub fn grid(rows: usize, cols: usize) -> Graph {
+    let right = (0..rows).flat_map(|r| (1..cols).map(move |c| (r * cols + c - 1, r * cols + c)));
+    let down = (1..rows).flat_map(|r| (0..cols).map(move |c| ((r - 1) * cols + c, r * cols + c)));
+    from_edges(rows * cols, right.chain(down))
+}
+
+/// The `dim` dimensional hypercube: `2^dim` nodes, connected when their ids
+/// differ by exactly one bit
+///
+/// Panics if `2^dim` does not fit in a `usize`.
+pub fn hypercube(dim: u32) -> Graph {
+    assert!(dim < usize::BITS, "Too many dimensions for a hypercube");
+    let n = 1usize << dim;
+    from_edges(
+        n,
+        (0..n).flat_map(|a| {
+            (0..dim)
+                .map(move |bit| (a, a ^ (1 << bit)))
+                .filter(|(a, b)| a < b)
+        }),
+    )
+}
+
+/// The Petersen graph: an outer 5-cycle on nodes `0..5`, an inner pentagram on
+/// nodes `5..10` and spokes between them
+pub fn petersen() -> Graph {
+    let outer = (0..5).map(|a| (a, (a + 1) % 5));
+    let spokes = (0..5).map(|a| (a, a + 5));
+    let inner = (0..5).map(|a| (a + 5, (a + 2) % 5 + 5));
+    from_edges(10, outer.chain(spokes).chain(inner))
+}
+
+/// The Erdős–Rényi G(n, p) random graph: every possible edge is included
+/// independently with probability `p`
+///
+/// Panics unless `0 <= p <= 1`.
+pub fn gnp<R: Rng>(n: usize, p: f64, rng: &mut R) -> Graph {
+    assert!(
+        (0.0..=1.0).contains(&p),
+        "The probability must be between 0 and 1"
+    );
+
+    let mut edges = vec![];
+    for a in 0..n {
+        for b in a + 1..n {
+            if rng.gen_bool(p) {
+                edges.push((a, b));
+            }
+        }
+    }
+
+    from_edges(n, edges)
+}
+
+/// The Erdős–Rényi G(n, m) random graph: `m` distinct edges chosen uniformly
+///
+/// Panics if `m` exceeds the number of possible edges.
+pub fn gnm<R: Rng>(n: usize, m: usize, rng: &mut R) -> Graph {
+    let max = n * n.saturating_sub(1) / 2;
+    assert!(m <= max, "Cannot place {} edges on {} nodes", m, n);
+
+    // When most edges are wanted, pick the ones to leave out instead
+    let wanted = if 2 * m > max { max - m } else { m };
+    let mut picked = HashSet::new();
+    while picked.len() < wanted {
+        let a = rng.gen_range(0..n);
+        let b = rng.gen_range(0..n);
+        if a != b {
+            picked.insert((a.min(b), a.max(b)));
+        }
+    }
+
+    let mut edges: Vec<(NodeId, NodeId)> = if wanted == m {
+        picked.into_iter().collect()
+    } else {
+        (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .filter(|e| !picked.contains(e))
+            .collect()
+    };
+    edges.sort_unstable();
+
+    from_edges(n, edges)
+}
+
+/// The Barabási–Albert preferential attachment graph. Starting from `m` isolated
+/// nodes, each new node is connected to `m` existing ones, picked with a probability
+/// proportional to their degree.
+///
+/// Panics unless `1 <= m < n`.
+pub fn barabasi_albert<R: Rng>(n: usize, m: usize, rng: &mut R) -> Graph {
+    assert!(m >= 1 && m < n, "Barabási–Albert graphs need 1 <= m < n");
+
+    let mut edges = vec![];
+    let mut targets: Vec<NodeId> = (0..m).collect();
+    // Every node appears here once for each of its edges
+    let mut repeated = vec![];
+    for source in m..n {
+        edges.extend(targets.iter().map(|t| (*t, source)));
+        repeated.extend(targets.iter().copied());
+        repeated.extend(std::iter::repeat_n(source, m));
+
+        let mut picked = HashSet::new();
+        targets.clear();
+        while targets.len() < m {
+            let candidate = repeated[rng.gen_range(0..repeated.len())];
+            if picked.insert(candidate) {
+                targets.push(candidate);
+            }
+        }
+    }
+
+    from_edges(n, edges)
+}
+
+/// The Watts–Strogatz small world graph. Nodes are placed on a ring and connected
+/// to their `k / 2` nearest neighbors on each side, then every edge is rewired to
+/// a random node with probability `beta`.
+///
+/// Panics unless `k` is even and smaller than `n`, and `0 <= beta <= 1`.
+pub fn watts_strogatz<R: Rng>(n: usize, k: usize, beta: f64, rng: &mut R) -> Graph {
+    assert!(k.is_multiple_of(2) && k < n, "Watts–Strogatz graphs need an even k < n");
+    assert!((0.0..=1.0).contains(&beta), "The probability must be between 0 and 1");
+
+    let key = |a: NodeId, b: NodeId| (a.min(b), a.max(b));
+    let mut edges: Vec<(NodeId, NodeId)> = (1..=k / 2)
+        .flat_map(|j| (0..n).map(move |a| (a, (a + j) % n)))
+        .collect();
+    let mut present: HashSet<(NodeId, NodeId)> = edges.iter().map(|(a, b)| key(*a, *b)).collect();
+    let mut degrees = vec![k; n];
+
+    for edge in edges.iter_mut() {
+        let (a, b) = *edge;
+        // A node already connected to everything cannot be rewired
+        if degrees[a] >= n - 1 || !rng.gen_bool(beta) {
+            continue;
+        }
+
+        let mut c = rng.gen_range(0..n);
+        while c == a || present.contains(&key(a, c)) {
+            c = rng.gen_range(0..n);
+        }
+        present.remove(&key(a, b));
+        present.insert(key(a, c));
+        degrees[b] -= 1;
+        degrees[c] += 1;
+        *edge = (a, c);
+    }
+
+    from_edges(n, edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn degrees(graph: &Graph) -> Vec<usize> {
+        (0..graph.node_count()).map(|n| graph.neighbors(n).count()).collect()
+    }
+
+    #[test]
+    fn deterministic_families() {
+        assert_eq!(complete(5).edge_count(), 10);
+        assert_eq!(path(4).edge_count(), 3);
+        assert_eq!(cycle(4).edge_count(), 4);
+        assert_eq!(cycle(2).edge_count(), 1);
+        assert_eq!(star(5).edge_count(), 4);
+        assert_eq!(wheel(5).edge_count(), 8);
+        assert_eq!(grid(3, 4).edge_count(), 17);
+        assert_eq!(hypercube(3).edge_count(), 12);
+        assert_eq!(degrees(&petersen()), vec![3; 10]);
+        assert_eq!(degrees(&wheel(5)), vec![4, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn gnm_edge_count() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(gnm(10, 12, &mut rng).edge_count(), 12);
+        assert_eq!(gnm(10, 40, &mut rng).edge_count(), 40);
+    }
+
+    #[test]
+    #[should_panic(expected = "between 0 and 1")]
+    fn gnp_rejects_invalid_probabilities() {
+        gnp(10, f64::NAN, &mut StdRng::seed_from_u64(1));
+    }
+
+    #[test]
+    fn random_generators_are_reproducible() {
+        let edges = |graph: Graph| graph.edges().map(|e| (e.from(), e.to())).collect::<Vec<_>>();
+        let ba = |seed| barabasi_albert(30, 2, &mut StdRng::seed_from_u64(seed));
+        let ws = |seed| watts_strogatz(30, 4, 0.2, &mut StdRng::seed_from_u64(seed));
+
+        assert_eq!(edges(ba(3)), edges(ba(3)));
+        assert_eq!(edges(ws(3)), edges(ws(3)));
+        assert_eq!(ba(3).edge_count(), 56);
+        assert_eq!(ws(3).edge_count(), 60);
+    }
+}
@@ -0,0 +1,471 @@
+//! Hamiltonian paths and cycles, which visit every node exactly once. Finding
+//! one is NP-complete, so every search takes a `stop` hook and calls it
+//! regularly. The search gives up with `Cancelled` once the hook returns `true`.
+//!
+//! Graphs of up to 20 nodes are solved by dynamic programming over subsets of
+//! nodes. Larger ones get a backtracking search that prunes on node degrees.
+//! Self loops and parallel edges are ignored.
+//!
+//! ## Example
+//! ```
+//! # use fluffy_structs::{generators, hamiltonian::hamiltonian_cycle};
+//! use std::time::{Duration, Instant};
+//!
+//! let start = Instant::now();
+//! let budget = || start.elapsed() > Duration::from_secs(1);
+//! let cycle = hamiltonian_cycle(&generators::hypercube(3), budget).unwrap();
+//! assert_eq!(cycle.map(|c| c.len()), Some(8));
+//! ```
+
+use crate::prelude::{IntoNeighbors, NodeCount, NodeId};
+
+/// The largest graph solved by dynamic programming
+const BITMASK_LIMIT: usize = 20;
+
+/// How many steps the searches take between two calls to the `stop` hook
+const CHECK_INTERVAL: usize = 1024;
+
+/// Returned when a search is stopped by its hook before finishing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cancelled;
+
+/// Calls the `stop` hook of a search once every `CHECK_INTERVAL` steps
+struct Ticker<F> {
+    stop: F,
+    steps: usize,
+}
+
+impl<F: FnMut() -> bool> Ticker<F> {
+    fn tick(&mut self) -> Result<(), Cancelled> {
+        self.steps += 1;
+        if self.steps.is_multiple_of(CHECK_INTERVAL) && (self.stop)() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct Search<F> {
+    successors: Vec<Vec<NodeId>>,
+    predecessors: Vec<Vec<NodeId>>,
+    ticker: Ticker<F>,
+}
+
+impl<F: FnMut() -> bool> Search<F> {
+    fn new<G: IntoNeighbors + NodeCount>(graph: &G, stop: F) -> Self {
+        let n = graph.node_count();
+        let mut successors = vec![vec![]; n];
+        let mut predecessors = vec![vec![]; n];
+        for (node, outgoing) in successors.iter_mut().enumerate() {
+            for next in graph.neighbors(node).filter(|next| *next != node) {
+                if !outgoing.contains(&next) {
+                    outgoing.push(next);
+                    predecessors[next].push(node);
+                }
+            }
+        }
+
+        Self {
+            successors,
+            predecessors,
+            ticker: Ticker { stop, steps: 0 },
+        }
+    }
+
+    fn masks(lists: &[Vec<NodeId>]) -> Vec<u32> {
+        lists
+            .iter()
+            .map(|list| list.iter().fold(0, |mask, n| mask | 1 << n))
+            .collect()
+    }
+
+    /// Dynamic programming: `ends[mask]` holds the nodes where a path covering
+    /// exactly the nodes of `mask` can end. Cycles start their paths at node 0.
+    fn bitmask(&mut self, cycle: bool) -> Result<Option<Vec<NodeId>>, Cancelled> {
+        let n = self.successors.len();
+        let successors = Self::masks(&self.successors);
+        let predecessors = Self::masks(&self.predecessors);
+        let full = (1usize << n) - 1;
+
+        let mut ends = vec![0u32; full + 1];
+        for node in 0..if cycle { 1 } else { n } {
+            ends[1 << node] = 1 << node;
+        }
+        for mask in 1..full {
+            self.ticker.tick()?;
+            let mut remaining = ends[mask];
+            while remaining != 0 {
+                let end = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                let mut next = successors[end] & !(mask as u32);
+                while next != 0 {
+                    let node = next.trailing_zeros() as usize;
+                    next &= next - 1;
+                    ends[mask | 1 << node] |= 1 << node;
+                }
+            }
+        }
+
+        let mut candidates = ends[full];
+        if cycle {
+            candidates &= predecessors[0];
+        }
+        if candidates == 0 {
+            return Ok(None);
+        }
+
+        let mut end = candidates.trailing_zeros() as usize;
+        let mut mask = full;
+        let mut path = vec![end];
+        while mask != 1 << end {
+            mask &= !(1 << end);
+            end = (ends[mask] & predecessors[end]).trailing_zeros() as usize;
+            path.push(end);
+        }
+        path.reverse();
+
+        Ok(Some(path))
+    }
+
+    /// Backtracking from `start`, trying the neighbors with the fewest free
+    /// successors first. The search keeps its own stack of the candidates left
+    /// at every node of the path, so long paths cannot overflow the call stack.
+    fn extend(&mut self, start: NodeId, cycle: bool) -> Result<Option<Vec<NodeId>>, Cancelled> {
+        let n = self.successors.len();
+        let closes = (0..n)
+            .map(|node| cycle && self.successors[node].contains(&start))
+            .collect();
+        let mut walk = Walk::new(&self.successors, &self.predecessors, closes);
+        walk.push(start);
+        let mut frames: Vec<(Vec<NodeId>, usize)> = vec![];
+
+        loop {
+            self.ticker.tick()?;
+            let last = *walk.path.last().unwrap();
+            if walk.path.len() == n {
+                if walk.closes[last] || !cycle {
+                    return Ok(Some(walk.path));
+                }
+                walk.pop();
+            } else if walk.feasible(cycle) {
+                frames.push((walk.candidates(), 0));
+            } else {
+                walk.pop();
+            }
+
+            // Step into the next candidate, leaving the nodes that have none left
+            loop {
+                let (candidates, cursor) = match frames.last_mut() {
+                    Some(frame) => frame,
+                    None => return Ok(None),
+                };
+                if let Some(&node) = candidates.get(*cursor) {
+                    *cursor += 1;
+                    walk.push(node);
+                    break;
+                }
+                frames.pop();
+                walk.pop();
+            }
+        }
+    }
+
+    fn backtrack(&mut self, cycle: bool) -> Result<Option<Vec<NodeId>>, Cancelled> {
+        let n = self.successors.len();
+        // A cycle can start anywhere, paths most likely start at a node with few
+        // ways in
+        let mut starts: Vec<NodeId> = (0..n).collect();
+        if cycle {
+            starts.truncate(1);
+        } else {
+            starts.sort_by_key(|node| self.predecessors[*node].len());
+        }
+
+        for start in starts {
+            if let Some(path) = self.extend(start, cycle)? {
+                return Ok(Some(path));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The path of a backtracking search, along with how many free nodes every node
+/// can be entered from and leave to, so that pruning takes constant time per
+/// edge instead of a scan of the whole graph
+struct Walk<'a> {
+    successors: &'a [Vec<NodeId>],
+    predecessors: &'a [Vec<NodeId>],
+    /// Whether a node can close the cycle by going back to the start
+    closes: Vec<bool>,
+    path: Vec<NodeId>,
+    free: Vec<bool>,
+    free_in: Vec<usize>,
+    free_out: Vec<usize>,
+    /// Free nodes without free predecessors
+    orphans: usize,
+    /// Free nodes without free successors, that cannot close the cycle either
+    dead_ends: usize,
+}
+
+impl<'a> Walk<'a> {
+    fn new(
+        successors: &'a [Vec<NodeId>],
+        predecessors: &'a [Vec<NodeId>],
+        closes: Vec<bool>,
+    ) -> Self {
+        let n = successors.len();
+        let free_in: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+        let free_out: Vec<usize> = successors.iter().map(Vec::len).collect();
+        let orphans = free_in.iter().filter(|d| **d == 0).count();
+        let dead_ends = (0..n).filter(|n| free_out[*n] == 0 && !closes[*n]).count();
+        Self {
+            successors,
+            predecessors,
+            closes,
+            path: vec![],
+            free: vec![true; n],
+            free_in,
+            free_out,
+            orphans,
+            dead_ends,
+        }
+    }
+
+    fn is_dead_end(&self, node: NodeId) -> bool {
+        self.free_out[node] == 0 && !self.closes[node]
+    }
+
+    fn push(&mut self, node: NodeId) {
+        let (successors, predecessors) = (self.successors, self.predecessors);
+        self.free[node] = false;
+        self.path.push(node);
+        if self.free_in[node] == 0 {
+            self.orphans -= 1;
+        }
+        if self.is_dead_end(node) {
+            self.dead_ends -= 1;
+        }
+
+        for &next in &successors[node] {
+            self.free_in[next] -= 1;
+            if self.free[next] && self.free_in[next] == 0 {
+                self.orphans += 1;
+            }
+        }
+        for &previous in &predecessors[node] {
+            self.free_out[previous] -= 1;
+            if self.free[previous] && self.is_dead_end(previous) {
+                self.dead_ends += 1;
+            }
+        }
+    }
+
+    fn pop(&mut self) {
+        let (successors, predecessors) = (self.successors, self.predecessors);
+        let node = self.path.pop().unwrap();
+        for &next in &successors[node] {
+            if self.free[next] && self.free_in[next] == 0 {
+                self.orphans -= 1;
+            }
+            self.free_in[next] += 1;
+        }
+        for &previous in &predecessors[node] {
+            if self.free[previous] && self.is_dead_end(previous) {
+                self.dead_ends -= 1;
+            }
+            self.free_out[previous] += 1;
+        }
+
+        self.free[node] = true;
+        if self.free_in[node] == 0 {
+            self.orphans += 1;
+        }
+        if self.is_dead_end(node) {
+            self.dead_ends += 1;
+        }
+    }
+
+    /// Degree pruning: every free node must still be enterable from the path's
+    /// end or another free node, and must be able to leave towards a free node,
+    /// except for a single last node of a path or for the way back of a cycle
+    fn feasible(&self, cycle: bool) -> bool {
+        let last = *self.path.last().unwrap();
+        let entered = self.successors[last]
+            .iter()
+            .filter(|n| self.free[**n] && self.free_in[**n] == 0)
+            .count();
+        entered == self.orphans && self.dead_ends <= if cycle { 0 } else { 1 }
+    }
+
+    /// The free successors of the path's end, fewest free successors first
+    fn candidates(&self) -> Vec<NodeId> {
+        let last = *self.path.last().unwrap();
+        let mut candidates: Vec<NodeId> = self.successors[last]
+            .iter()
+            .copied()
+            .filter(|n| self.free[*n])
+            .collect();
+        candidates.sort_by_key(|node| self.free_out[*node]);
+        candidates
+    }
+}
+
+/// Searches for a path visiting every node once. Returns `Ok(None)` if there is none.
+pub fn hamiltonian_path<G, F>(graph: &G, stop: F) -> Result<Option<Vec<NodeId>>, Cancelled>
+where
+    G: IntoNeighbors + NodeCount,
+    F: FnMut() -> bool,
+{
+    let mut search = Search::new(graph, stop);
+    match graph.node_count() {
+        0 => Ok(Some(vec![])),
+        n if n <= BITMASK_LIMIT => search.bitmask(false),
+        _ => search.backtrack(false),
+    }
+}
+
+/// Searches for a cycle visiting every node once, given as the list of its nodes
+/// starting with node 0. Returns `Ok(None)` if there is none. Cycles need at
+/// least two nodes on directed graphs and three on undirected ones.
+pub fn hamiltonian_cycle<G, F>(graph: &G, stop: F) -> Result<Option<Vec<NodeId>>, Cancelled>
+where
+    G: IntoNeighbors + NodeCount,
+    F: FnMut() -> bool,
+{
+    let n = graph.node_count();
+    if n < 2 || (n == 2 && !graph.is_directed()) {
+        return Ok(None);
+    }
+
+    let mut search = Search::new(graph, stop);
+    if search.predecessors.iter().any(|p| p.is_empty()) {
+        return Ok(None);
+    }
+    if n <= BITMASK_LIMIT {
+        search.bitmask(true)
+    } else {
+        search.backtrack(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn is_path<G: IntoNeighbors + NodeCount>(graph: &G, path: &[NodeId], cycle: bool) -> bool {
+        let mut sorted = path.to_vec();
+        sorted.sort_unstable();
+        let adjacent = |a: NodeId, b: NodeId| graph.neighbors(a).any(|n| n == b);
+        sorted == (0..graph.node_count()).collect::<Vec<_>>()
+            && path.windows(2).all(|w| adjacent(w[0], w[1]))
+            && (!cycle || adjacent(*path.last().unwrap(), path[0]))
+    }
+
+    #[test]
+    fn small_graphs() {
+        let petersen = generators::petersen();
+        let path = hamiltonian_path(&petersen, || false).unwrap().unwrap();
+        assert!(is_path(&petersen, &path, false));
+        assert_eq!(hamiltonian_cycle(&petersen, || false), Ok(None));
+
+        let cycle = hamiltonian_cycle(&generators::wheel(7), || false)
+            .unwrap()
+            .unwrap();
+        assert!(is_path(&generators::wheel(7), &cycle, true));
+        assert_eq!(hamiltonian_path(&generators::star(4), || false), Ok(None));
+        assert_eq!(hamiltonian_cycle(&generators::path(2), || false), Ok(None));
+    }
+
+    #[test]
+    fn directed() {
+        let mut graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        graph.add_edge(2, 0, 1);
+        graph.add_edge(0, 3, 1);
+        graph.add_edge(3, 1, 1);
+        assert_eq!(
+            hamiltonian_path(&graph, || false),
+            Ok(Some(vec![2, 0, 3, 1]))
+        );
+        assert_eq!(hamiltonian_cycle(&graph, || false), Ok(None));
+        graph.add_edge(1, 2, 1);
+        assert_eq!(
+            hamiltonian_cycle(&graph, || false),
+            Ok(Some(vec![0, 3, 1, 2]))
+        );
+    }
+
+    #[test]
+    fn large_graphs_backtrack() {
+        let grid = generators::grid(5, 6);
+        let cycle = hamiltonian_cycle(&grid, || false).unwrap().unwrap();
+        assert!(is_path(&grid, &cycle, true));
+
+        let grid = generators::grid(5, 5);
+        let path = hamiltonian_path(&grid, || false).unwrap().unwrap();
+        assert!(is_path(&grid, &path, false));
+    }
+
+    #[test]
+    fn backtracking_agrees_with_bitmask() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for round in 0..200 {
+            let mut graph = if round % 2 == 0 {
+                Graph::new()
+            } else {
+                Graph::new_undirected()
+            };
+            for _ in 0..10 {
+                graph.add_node(());
+            }
+            for _ in 0..rng.gen_range(10..30) {
+                graph.add_edge(rng.gen_range(0..10), rng.gen_range(0..10), 1);
+            }
+
+            for cycle in [false, true] {
+                let exact = Search::new(&graph, || false).bitmask(cycle).unwrap();
+                let found = Search::new(&graph, || false).backtrack(cycle).unwrap();
+                assert_eq!(exact.is_some(), found.is_some());
+                if let Some(path) = found {
+                    assert!(is_path(&graph, &path, cycle));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_paths_do_not_overflow() {
+        let path = generators::path(100_000);
+        let found = hamiltonian_path(&path, || false).unwrap().unwrap();
+        assert_eq!(found.len(), 100_000);
+        assert!(found.windows(2).all(|w| w[0].abs_diff(w[1]) == 1));
+
+        let cycle = hamiltonian_cycle(&generators::cycle(100_000), || false);
+        assert_eq!(cycle.unwrap().map(|c| c.len()), Some(100_000));
+    }
+
+    #[test]
+    fn searches_can_be_cancelled() {
+        let mut calls = 0;
+        let stop = || {
+            calls += 1;
+            calls > 3
+        };
+        assert_eq!(
+            hamiltonian_cycle(&generators::complete(20), stop),
+            Err(Cancelled)
+        );
+        assert_eq!(calls, 4);
+        assert_eq!(
+            hamiltonian_path(&generators::complete(20), || true),
+            Err(Cancelled)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use super::{EdgeId, Graph, NodeId};
+use crate::prelude::{GraphBase, IntoNeighbors, NodeCount, Visitable};
+
+/// A hypergraph, whose edges join any number of nodes. Nodes hold values of type
+/// `N` and hyperedges values of type `E`.
+///
+/// As far as the traits of `prelude` go, two nodes are neighbors when they share
+/// a hyperedge, so graph algorithms see the clique expansion.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::Hypergraph;
+/// let mut hypergraph = Hypergraph::new();
+/// let a = hypergraph.add_node("a");
+/// let b = hypergraph.add_node("b");
+/// let c = hypergraph.add_node("c");
+/// let meeting = hypergraph.add_edge(vec![a, b, c], "meeting");
+/// hypergraph.add_edge(vec![a, b], "call");
+///
+/// assert_eq!(hypergraph.members(meeting), &[a, b, c]);
+/// assert_eq!(hypergraph.degree(a), 2);
+/// assert_eq!(hypergraph.incident_edges(c), &[meeting]);
+/// ```
+pub struct Hypergraph<N = (), E = ()> {
+    nodes: Vec<N>,
+    edges: Vec<E>,
+    members: Vec<Vec<NodeId>>,
+    incidence: Vec<Vec<EdgeId>>,
+}
+
+/// A node of the incidence graph of a hypergraph, standing for either one of
+/// its nodes or one of its hyperedges
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incidence<N, E> {
+    Node(N),
+    Edge(E),
+}
+
+impl<N, E> Default for Hypergraph<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E> Hypergraph<N, E> {
+    /// Creates an empty hypergraph
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            edges: vec![],
+            members: vec![],
+            incidence: vec![],
+        }
+    }
+
+    /// Adds a node holding `value` and returns its id
+    pub fn add_node(&mut self, value: N) -> NodeId {
+        self.nodes.push(value);
+        self.incidence.push(vec![]);
+        self.nodes.len() - 1
+    }
+
+    /// Adds a hyperedge joining `nodes` and returns its id. Nodes listed twice
+    /// are only counted once.
+    ///
+    /// Panics if a node does not exist.
+    pub fn add_edge<I: IntoIterator<Item = NodeId>>(&mut self, nodes: I, value: E) -> EdgeId {
+        let id = self.edges.len();
+        let mut members: Vec<NodeId> = nodes.into_iter().collect();
+        members.sort_unstable();
+        members.dedup();
+        assert!(
+            members.last().is_none_or(|n| *n < self.nodes.len()),
+            "Cannot add an edge between nodes that do not exist"
+        );
+
+        for &node in &members {
+            self.incidence[node].push(id);
+        }
+        self.edges.push(value);
+        self.members.push(members);
+        id
+    }
+
+    /// The number of nodes in the hypergraph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of hyperedges in the hypergraph
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns the value stored in node `id`, if any
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id)
+    }
+
+    /// Returns the value stored in hyperedge `id`, if any
+    pub fn edge(&self, id: EdgeId) -> Option<&E> {
+        self.edges.get(id)
+    }
+
+    /// The nodes joined by hyperedge `edge`, sorted
+    pub fn members(&self, edge: EdgeId) -> &[NodeId] {
+        &self.members[edge]
+    }
+
+    /// The hyperedges containing `node`, in id order
+    pub fn incident_edges(&self, node: NodeId) -> &[EdgeId] {
+        &self.incidence[node]
+    }
+
+    /// The number of hyperedges containing `node`
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.incidence[node].len()
+    }
+
+    /// The other nodes sharing at least one hyperedge with `node`, sorted
+    pub fn neighbors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut neighbors: Vec<NodeId> = self.incidence[node]
+            .iter()
+            .flat_map(|e| self.members[*e].iter().copied())
+            .filter(|n| *n != node)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Builds the dual hypergraph, where every hyperedge becomes a node and every
+    /// node a hyperedge joining the hyperedges that contained it. Ids are kept.
+    pub fn dual(&self) -> Hypergraph<E, N>
+    where
+        N: Clone,
+        E: Clone,
+    {
+        let mut dual = Hypergraph::new();
+        for value in &self.edges {
+            dual.add_node(value.clone());
+        }
+        for (node, value) in self.nodes.iter().enumerate() {
+            dual.add_edge(self.incidence[node].iter().copied(), value.clone());
+        }
+
+        dual
+    }
+
+    /// Builds the bipartite incidence graph, which links every node to the
+    /// hyperedges containing it. Node `i` keeps id `i`, and hyperedge `j` gets
+    /// id `node_count() + j`.
+    pub fn incidence_graph(&self) -> Graph<Incidence<N, E>>
+    where
+        N: Clone,
+        E: Clone,
+    {
+        let mut graph = Graph::new_undirected();
+        for value in &self.nodes {
+            graph.add_node(Incidence::Node(value.clone()));
+        }
+        for (edge, value) in self.edges.iter().enumerate() {
+            let id = graph.add_node(Incidence::Edge(value.clone()));
+            for &node in &self.members[edge] {
+                graph.add_edge(node, id, 1);
+            }
+        }
+
+        graph
+    }
+
+    /// Builds the clique expansion, an undirected graph with the same nodes and
+    /// an edge between every two nodes sharing a hyperedge. Edges weigh the
+    /// number of hyperedges their endpoints share.
+    pub fn clique_expansion(&self) -> Graph<N>
+    where
+        N: Clone,
+    {
+        let mut graph = Graph::new_undirected();
+        for value in &self.nodes {
+            graph.add_node(value.clone());
+        }
+        for members in &self.members {
+            for (i, &a) in members.iter().enumerate() {
+                for &b in &members[i + 1..] {
+                    let shared = graph.find_edge(a, b).map_or(0, |e| graph.edges[e].weight);
+                    graph.update_edge(a, b, shared + 1);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+impl<N, E> GraphBase for Hypergraph<N, E> {
+    fn is_directed(&self) -> bool {
+        false
+    }
+}
+
+impl<N, E> NodeCount for Hypergraph<N, E> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<N, E> IntoNeighbors for Hypergraph<N, E> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(Hypergraph::neighbors(self, node).into_iter())
+    }
+}
+
+impl<N, E> Visitable for Hypergraph<N, E> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        vec![false; self.nodes.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algo::connected_components, prelude::Node};
+
+    fn make_test_hypergraph() -> Hypergraph<char, u32> {
+        let mut hypergraph = Hypergraph::new();
+        for value in "abcde".chars() {
+            hypergraph.add_node(value);
+        }
+        hypergraph.add_edge(vec![0, 1, 2], 10);
+        hypergraph.add_edge(vec![1, 2], 20);
+        hypergraph.add_edge(vec![3, 3], 30);
+        hypergraph
+    }
+
+    #[test]
+    fn incidence_queries() {
+        let hypergraph = make_test_hypergraph();
+        assert_eq!(hypergraph.members(2), &[3]);
+        assert_eq!(hypergraph.incident_edges(1), &[0, 1]);
+        assert_eq!(hypergraph.degree(4), 0);
+        assert_eq!(hypergraph.neighbors(0), vec![1, 2]);
+        assert_eq!(connected_components(&hypergraph), vec![0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn dual() {
+        let dual = make_test_hypergraph().dual();
+        assert_eq!((dual.node_count(), dual.edge_count()), (3, 5));
+        assert_eq!(dual.node(1), Some(&20));
+        assert_eq!(dual.members(1), &[0, 1]);
+        assert_eq!(dual.edge(4), Some(&'e'));
+        assert!(dual.members(4).is_empty());
+    }
+
+    #[test]
+    fn conversions() {
+        let hypergraph = make_test_hypergraph();
+        let incidence = hypergraph.incidence_graph();
+        assert_eq!((incidence.node_count(), incidence.edge_count()), (8, 6));
+        assert_eq!(*incidence.node(6).unwrap().value(), Incidence::Edge(20));
+        assert_eq!(incidence.neighbors(1).collect::<Vec<_>>(), vec![5, 6]);
+
+        let expansion = hypergraph.clique_expansion();
+        assert_eq!(expansion.edge_count(), 3);
+        let bc = expansion.find_edge(2, 1).unwrap();
+        assert_eq!(expansion.edge(bc).unwrap().weight(), 2);
+    }
+}
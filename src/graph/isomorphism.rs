@@ -0,0 +1,494 @@
+//! Graph isomorphism and induced subgraph isomorphism, based on the VF2 state
+//! space search with a VF2++ style node ordering.
+//!
+//! Mappings are reported as vectors indexed by the nodes of the first (or pattern)
+//! graph, holding the matching node of the second (or target) graph. The search
+//! is meant for simple graphs: parallel edges are only checked for their count.
+
+use std::collections::HashMap;
+
+use crate::prelude::{EdgeId, IntoEdges, NodeCount, NodeId};
+
+/// Returns `true` if `a` and `b` have the same structure, ignoring node values
+/// and edge weights
+pub fn is_isomorphic<G1, G2>(a: &G1, b: &G2) -> bool
+where
+    G1: IntoEdges + NodeCount,
+    G2: IntoEdges + NodeCount,
+{
+    isomorphism(a, b).is_some()
+}
+
+/// Finds a mapping of the nodes of `a` onto the nodes of `b` that preserves
+/// edges, ignoring node values and edge weights
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{generators, isomorphism::isomorphism};
+/// let mapping = isomorphism(&generators::cycle(4), &generators::grid(2, 2));
+/// assert_eq!(mapping, Some(vec![0, 1, 3, 2]));
+/// ```
+pub fn isomorphism<G1, G2>(a: &G1, b: &G2) -> Option<Vec<NodeId>>
+where
+    G1: IntoEdges + NodeCount,
+    G2: IntoEdges + NodeCount,
+{
+    isomorphism_matching(a, b, |_, _| true, |_, _| true)
+}
+
+/// Returns `true` if `a` and `b` have the same structure, comparing nodes with
+/// `node_eq` and edges with `edge_eq`
+pub fn is_isomorphic_matching<G1, G2, NF, EF>(a: &G1, b: &G2, node_eq: NF, edge_eq: EF) -> bool
+where
+    G1: IntoEdges + NodeCount,
+    G2: IntoEdges + NodeCount,
+    NF: FnMut(NodeId, NodeId) -> bool,
+    EF: FnMut(EdgeId, EdgeId) -> bool,
+{
+    isomorphism_matching(a, b, node_eq, edge_eq).is_some()
+}
+
+/// Same as `isomorphism`, comparing nodes with `node_eq` and edges with
+/// `edge_eq`. Both receive a node or edge id of `a` first and one of `b` second,
+/// and can look up whatever the graphs store for them.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{isomorphism::isomorphism_matching, Graph};
+/// let mut a = Graph::new();
+/// let mut b = Graph::new();
+/// for name in ['x', 'y'] {
+///     a.add_node(name);
+/// }
+/// for name in ['y', 'x'] {
+///     b.add_node(name);
+/// }
+/// a.add_edge(0, 1, 4);
+/// b.add_edge(1, 0, 4);
+///
+/// let name = |graph: &Graph<char>, node| **graph.node(node).unwrap();
+/// let weight = |graph: &Graph<char>, edge| graph.edge(edge).unwrap().weight();
+/// let mapping = isomorphism_matching(
+///     &a,
+///     &b,
+///     |x, y| name(&a, x) == name(&b, y),
+///     |e, f| weight(&a, e) == weight(&b, f),
+/// );
+/// assert_eq!(mapping, Some(vec![1, 0]));
+/// ```
+pub fn isomorphism_matching<G1, G2, NF, EF>(
+    a: &G1,
+    b: &G2,
+    node_eq: NF,
+    edge_eq: EF,
+) -> Option<Vec<NodeId>>
+where
+    G1: IntoEdges + NodeCount,
+    G2: IntoEdges + NodeCount,
+    NF: FnMut(NodeId, NodeId) -> bool,
+    EF: FnMut(EdgeId, EdgeId) -> bool,
+{
+    let (a, b) = (Adjacency::new(a), Adjacency::new(b));
+    if a.node_count() != b.node_count() || a.entry_count() != b.entry_count() {
+        return None;
+    }
+
+    Vf2::new(a, b, node_eq, edge_eq, Mode::Isomorphism).next()
+}
+
+/// Returns an iterator over every mapping of `pattern` onto an induced subgraph
+/// of `target`, comparing nodes with `node_eq` and edges with `edge_eq` as
+/// `isomorphism_matching` does
+pub fn subgraph_isomorphisms_iter<G1, G2, NF, EF>(
+    pattern: &G1,
+    target: &G2,
+    node_eq: NF,
+    edge_eq: EF,
+) -> Vf2<NF, EF>
+where
+    G1: IntoEdges + NodeCount,
+    G2: IntoEdges + NodeCount,
+    NF: FnMut(NodeId, NodeId) -> bool,
+    EF: FnMut(EdgeId, EdgeId) -> bool,
+{
+    Vf2::new(
+        Adjacency::new(pattern),
+        Adjacency::new(target),
+        node_eq,
+        edge_eq,
+        Mode::Subgraph,
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Isomorphism,
+    Subgraph,
+}
+
+struct Frame {
+    candidates: Vec<NodeId>,
+    next: usize,
+}
+
+/// The neighbors of every node of one of the two graphs, gathered once before
+/// the search. Undirected graphs list the same neighbors both ways.
+struct Adjacency {
+    directed: bool,
+    outgoing: Vec<Vec<NodeId>>,
+    incoming: Vec<Vec<NodeId>>,
+    /// The first edge going from a node to another
+    edges: HashMap<(NodeId, NodeId), EdgeId>,
+}
+
+impl Adjacency {
+    fn new<G: IntoEdges + NodeCount>(graph: &G) -> Self {
+        let n = graph.node_count();
+        let directed = graph.is_directed();
+        let mut outgoing = vec![vec![]; n];
+        let mut incoming = vec![vec![]; n];
+        let mut edges = HashMap::new();
+        for (node, targets) in outgoing.iter_mut().enumerate() {
+            for edge in graph.edges(node) {
+                targets.push(edge.target);
+                if directed {
+                    incoming[edge.target].push(node);
+                }
+                edges.entry((node, edge.target)).or_insert(edge.id);
+            }
+        }
+        if !directed {
+            incoming = outgoing.clone();
+        }
+
+        Self {
+            directed,
+            outgoing,
+            incoming,
+            edges,
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    fn entry_count(&self) -> usize {
+        self.outgoing.iter().map(Vec::len).sum()
+    }
+
+    fn degree(&self, node: NodeId) -> usize {
+        self.outgoing[node].len() + self.incoming[node].len()
+    }
+}
+
+/// An iterator over the node mappings found by the VF2 search
+pub struct Vf2<NF, EF> {
+    g1: Adjacency,
+    g2: Adjacency,
+    node_eq: NF,
+    edge_eq: EF,
+    mode: Mode,
+    order: Vec<NodeId>,
+    core_1: Vec<Option<NodeId>>,
+    core_2: Vec<Option<NodeId>>,
+    stack: Vec<Frame>,
+    started: bool,
+}
+
+impl<NF, EF> Vf2<NF, EF>
+where
+    NF: FnMut(NodeId, NodeId) -> bool,
+    EF: FnMut(EdgeId, EdgeId) -> bool,
+{
+    fn new(g1: Adjacency, g2: Adjacency, node_eq: NF, edge_eq: EF, mode: Mode) -> Self {
+        Self {
+            order: matching_order(&g1),
+            core_1: vec![None; g1.node_count()],
+            core_2: vec![None; g2.node_count()],
+            stack: vec![],
+            // Graphs that cannot match start out exhausted
+            started: g1.directed != g2.directed || g1.node_count() > g2.node_count(),
+            g1,
+            g2,
+            node_eq,
+            edge_eq,
+            mode,
+        }
+    }
+
+    /// Nodes of `g2` worth trying as a match for the `depth`-th node in the order
+    fn candidates(&self, depth: usize) -> Vec<NodeId> {
+        let u = self.order[depth];
+        let mapped_out = self.g1.incoming[u]
+            .iter()
+            .find_map(|w| self.core_1[*w].map(|x| &self.g2.outgoing[x]));
+        let mapped = mapped_out.or_else(|| {
+            self.g1.outgoing[u]
+                .iter()
+                .find_map(|w| self.core_1[*w].map(|x| &self.g2.incoming[x]))
+        });
+
+        let mut candidates: Vec<NodeId> = match mapped {
+            Some(neighbors) => neighbors.clone(),
+            None => (0..self.g2.node_count()).collect(),
+        };
+        candidates.retain(|v| self.core_2[*v].is_none());
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+
+    fn edges_match(&mut self, from_1: NodeId, to_1: NodeId, from_2: NodeId, to_2: NodeId) -> bool {
+        match (
+            self.g1.edges.get(&(from_1, to_1)),
+            self.g2.edges.get(&(from_2, to_2)),
+        ) {
+            (Some(e1), Some(e2)) => (self.edge_eq)(*e1, *e2),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn feasible(&mut self, u: NodeId, v: NodeId) -> bool {
+        let (g1, g2) = (&self.g1, &self.g2);
+        let mode = self.mode;
+        let degrees_fit = |a: usize, b: usize| match mode {
+            Mode::Isomorphism => a == b,
+            Mode::Subgraph => a <= b,
+        };
+        if !degrees_fit(g1.outgoing[u].len(), g2.outgoing[v].len())
+            || !degrees_fit(g1.incoming[u].len(), g2.incoming[v].len())
+        {
+            return false;
+        }
+
+        // Every edge between `u` and the mapped nodes must exist between `v` and
+        // their images, and vice versa since subgraphs are induced
+        let mapped_1 = |w: &&NodeId| **w != u && self.core_1[**w].is_some();
+        let mapped_2 = |x: &&NodeId| **x != v && self.core_2[**x].is_some();
+        let out_1: Vec<NodeId> = g1.outgoing[u].iter().filter(mapped_1).copied().collect();
+        let in_1: Vec<NodeId> = g1.incoming[u].iter().filter(mapped_1).copied().collect();
+        let out_2 = g2.outgoing[v].iter().filter(mapped_2).count();
+        let in_2 = g2.incoming[v].iter().filter(mapped_2).count();
+        if out_1.len() != out_2 || in_1.len() != in_2 {
+            return false;
+        }
+
+        if !(self.node_eq)(u, v) || !self.edges_match(u, u, v, v) {
+            return false;
+        }
+        for w in out_1 {
+            let x = self.core_1[w].unwrap();
+            if !self.edges_match(u, w, v, x) {
+                return false;
+            }
+        }
+        if self.g1.directed {
+            for w in in_1 {
+                let x = self.core_1[w].unwrap();
+                if !self.edges_match(w, u, x, v) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl<NF, EF> Iterator for Vf2<NF, EF>
+where
+    NF: FnMut(NodeId, NodeId) -> bool,
+    EF: FnMut(EdgeId, EdgeId) -> bool,
+{
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.order.is_empty() {
+                return Some(vec![]);
+            }
+            let candidates = self.candidates(0);
+            self.stack.push(Frame {
+                candidates,
+                next: 0,
+            });
+        }
+
+        while !self.stack.is_empty() {
+            let depth = self.stack.len() - 1;
+            let u = self.order[depth];
+            if let Some(v) = self.core_1[u].take() {
+                self.core_2[v] = None;
+            }
+
+            let mut found = None;
+            while let Some(&v) = self.stack[depth].candidates.get(self.stack[depth].next) {
+                self.stack[depth].next += 1;
+                if self.feasible(u, v) {
+                    found = Some(v);
+                    break;
+                }
+            }
+
+            match found {
+                None => {
+                    self.stack.pop();
+                }
+                Some(v) => {
+                    self.core_1[u] = Some(v);
+                    self.core_2[v] = Some(u);
+                    if depth + 1 == self.order.len() {
+                        return Some(self.core_1.iter().map(|v| v.unwrap()).collect());
+                    }
+                    let candidates = self.candidates(depth + 1);
+                    self.stack.push(Frame {
+                        candidates,
+                        next: 0,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Orders the pattern nodes so that each one is as connected as possible to the
+/// ones before it, which lets the search prune early
+fn matching_order(graph: &Adjacency) -> Vec<NodeId> {
+    let n = graph.node_count();
+    let mut ordered = vec![false; n];
+    let mut links = vec![0usize; n];
+    let mut order = Vec::with_capacity(n);
+
+    while order.len() < n {
+        let next = (0..n)
+            .filter(|node| !ordered[*node])
+            .max_by_key(|node| (links[*node], graph.degree(*node), std::cmp::Reverse(*node)))
+            .unwrap();
+        ordered[next] = true;
+        order.push(next);
+        for neighbor in graph.outgoing[next].iter().chain(&graph.incoming[next]) {
+            links[*neighbor] += 1;
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph, MatrixGraph};
+
+    fn relabel(graph: &Graph, permutation: &[NodeId]) -> Graph {
+        let mut relabeled = Graph::new_undirected();
+        for _ in 0..graph.node_count() {
+            relabeled.add_node(());
+        }
+        for edge in graph.edges() {
+            relabeled.add_edge(
+                permutation[edge.from()],
+                permutation[edge.to()],
+                edge.weight(),
+            );
+        }
+
+        relabeled
+    }
+
+    #[test]
+    fn isomorphic_petersen() {
+        let petersen = generators::petersen();
+        let shuffled = relabel(&petersen, &[3, 7, 1, 9, 0, 5, 2, 8, 6, 4]);
+        let mapping = isomorphism(&petersen, &shuffled).unwrap();
+        for edge in petersen.edges() {
+            assert!(shuffled
+                .find_edge(mapping[edge.from()], mapping[edge.to()])
+                .is_some());
+        }
+        assert!(!is_isomorphic(&petersen, &generators::grid(2, 5)));
+    }
+
+    #[test]
+    fn directed_and_matching() {
+        let mut a = Graph::new();
+        let mut b = Graph::new();
+        for value in 0..3 {
+            a.add_node(value);
+            b.add_node(value);
+        }
+        a.add_edge(0, 1, 1);
+        a.add_edge(1, 2, 2);
+        b.add_edge(1, 2, 1);
+        b.add_edge(2, 0, 2);
+
+        let value = |graph: &Graph<i32>, node| **graph.node(node).unwrap();
+        let weight = |graph: &Graph<i32>, edge| graph.edge(edge).unwrap().weight();
+        let same_values = |x, y| value(&a, x) == value(&b, y);
+        let same_weights = |e, f| weight(&a, e) == weight(&b, f);
+
+        assert!(is_isomorphic(&a, &b));
+        assert!(!is_isomorphic_matching(&a, &b, same_values, |_, _| true));
+        assert_eq!(
+            isomorphism_matching(&a, &b, |_, _| true, same_weights),
+            Some(vec![1, 2, 0])
+        );
+        assert!(!is_isomorphic_matching(
+            &a,
+            &b,
+            |_, _| true,
+            |e, f| { weight(&a, e) != weight(&b, f) }
+        ));
+    }
+
+    #[test]
+    fn other_representations() {
+        let mut matrix = MatrixGraph::new_undirected();
+        for _ in 0..5 {
+            matrix.add_node(());
+        }
+        for node in 0..5 {
+            matrix.add_edge(node, (node + 2) % 5, 1);
+        }
+        assert!(is_isomorphic(&matrix, &generators::cycle(5)));
+        assert!(!is_isomorphic(&matrix, &generators::path(5)));
+
+        let mut directed = Graph::new();
+        for _ in 0..4 {
+            directed.add_node(());
+        }
+        for node in 0..4 {
+            directed.add_edge(node, (node + 1) % 4, 1);
+        }
+        let undirected = directed.undirected_view();
+        assert_eq!(
+            subgraph_isomorphisms_iter(&generators::path(2), &undirected, |_, _| true, |_, _| true)
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn subgraph_mappings() {
+        let triangle = generators::complete(3);
+        let k4 = generators::complete(4);
+        let count = subgraph_isomorphisms_iter(&triangle, &k4, |_, _| true, |_, _| true).count();
+        assert_eq!(count, 24);
+
+        // Induced subgraphs only: a path of three nodes does not fit in a triangle
+        let path = generators::path(3);
+        assert_eq!(
+            subgraph_isomorphisms_iter(&path, &k4, |_, _| true, |_, _| true).count(),
+            0
+        );
+        assert_eq!(
+            subgraph_isomorphisms_iter(&path, &generators::cycle(5), |_, _| true, |_, _| true)
+                .count(),
+            10
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Node placement for drawing graphs, and an SVG writer using it. Layouts return
+//! one `(x, y)` position per node, indexed by node id, in whatever units suit the
+//! algorithm. `to_svg` scales them to fit its canvas.
+
+use std::fmt::{Display, Write};
+
+use rand::Rng;
+
+use super::Graph;
+use crate::{
+    algo::{toposort, Cycle},
+    prelude::{IntoNeighbors, Node, NodeCount, NodeId},
+};
+
+/// How many barycenter sweeps `sugiyama` makes to reduce crossings
+const SWEEPS: usize = 12;
+
+/// Spreads nodes with the Fruchterman–Reingold force model: every pair of nodes
+/// repels, edges pull their endpoints together, and the moves shrink as the
+/// layout cools down. Positions start at random and stay in the unit square.
+pub fn fruchterman_reingold<G: IntoNeighbors + NodeCount, R: Rng>(
+    graph: &G,
+    iterations: usize,
+    rng: &mut R,
+) -> Vec<(f64, f64)> {
+    let n = graph.node_count();
+    let mut positions: Vec<(f64, f64)> = (0..n).map(|_| (rng.gen(), rng.gen())).collect();
+    if n < 2 {
+        return positions;
+    }
+
+    // The ideal distance between nodes, so that they evenly cover the square
+    let k = (1.0 / n as f64).sqrt();
+    let mut temperature = 0.1;
+    let cooling = temperature / (iterations as f64 + 1.0);
+
+    for _ in 0..iterations {
+        let mut moves = vec![(0.0, 0.0); n];
+        for a in 0..n {
+            for b in a + 1..n {
+                let (dx, dy, distance) = offset(positions[a], positions[b]);
+                let force = k * k / distance;
+                moves[a].0 += dx / distance * force;
+                moves[a].1 += dy / distance * force;
+                moves[b].0 -= dx / distance * force;
+                moves[b].1 -= dy / distance * force;
+            }
+        }
+
+        for a in 0..n {
+            // Undirected edges are seen from both ends, pull them once
+            for b in graph.neighbors(a) {
+                if a == b || (!graph.is_directed() && b < a) {
+                    continue;
+                }
+                let (dx, dy, distance) = offset(positions[a], positions[b]);
+                let force = distance * distance / k;
+                moves[a].0 -= dx / distance * force;
+                moves[a].1 -= dy / distance * force;
+                moves[b].0 += dx / distance * force;
+                moves[b].1 += dy / distance * force;
+            }
+        }
+
+        for (position, (mx, my)) in positions.iter_mut().zip(moves) {
+            let length = (mx * mx + my * my).sqrt().max(f64::EPSILON);
+            let step = length.min(temperature);
+            position.0 = (position.0 + mx / length * step).clamp(0.0, 1.0);
+            position.1 = (position.1 + my / length * step).clamp(0.0, 1.0);
+        }
+        temperature -= cooling;
+    }
+
+    positions
+}
+
+/// The vector from `b` to `a` and its length, kept away from zero so that
+/// overlapping nodes can still be pushed apart
+fn offset(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = (dx * dx + dy * dy).sqrt().max(1e-6);
+    (dx, dy, distance)
+}
+
+/// Draws a directed acyclic graph in horizontal layers, with every edge pointing
+/// downwards. Nodes are layered by their longest path from a source. The order
+/// inside each layer is then improved by barycenter sweeps to reduce crossings.
+/// Node `i` of a layer is at `x = i` (centered on the layer) and the `y` of the
+/// layer is its depth.
+///
+/// Fails if the graph has a cycle. Panics if the graph is undirected.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, layout::sugiyama};
+/// let mut graph = Graph::new();
+/// for _ in 0..3 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 1);
+/// graph.add_edge(0, 2, 1);
+/// assert_eq!(sugiyama(&graph), Ok(vec![(0.0, 0.0), (-0.5, 1.0), (0.5, 1.0)]));
+/// ```
+pub fn sugiyama<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Vec<(f64, f64)>, Cycle> {
+    assert!(graph.is_directed(), "Sugiyama needs a directed graph");
+    let n = graph.node_count();
+    let order = toposort(graph)?;
+    let mut depth = vec![0; n];
+    for &node in &order {
+        for next in graph.neighbors(node) {
+            depth[next] = depth[next].max(depth[node] + 1);
+        }
+    }
+
+    // Edges spanning several layers go through a dummy node on each of them
+    let mut layers = vec![vec![]; depth.iter().max().map_or(0, |d| d + 1)];
+    let mut above: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut below: Vec<Vec<usize>> = vec![vec![]; n];
+    for node in 0..n {
+        layers[depth[node]].push(node);
+    }
+    for node in 0..n {
+        let mut targets: Vec<NodeId> = graph.neighbors(node).collect();
+        targets.sort_unstable();
+        targets.dedup();
+        for target in targets {
+            let mut upper = node;
+            let spanned = depth[node] + 1..depth[target];
+            for layer in &mut layers[spanned] {
+                let dummy = above.len();
+                above.push(vec![upper]);
+                below.push(vec![]);
+                below[upper].push(dummy);
+                layer.push(dummy);
+                upper = dummy;
+            }
+            above[target].push(upper);
+            below[upper].push(target);
+        }
+    }
+
+    let mut best = (crossings(&layers, &below), layers.clone());
+    for sweep in 0..SWEEPS {
+        if sweep % 2 == 0 {
+            for i in 1..layers.len() {
+                reorder(&mut layers, i, i - 1, &above);
+            }
+        } else {
+            for i in (0..layers.len().saturating_sub(1)).rev() {
+                reorder(&mut layers, i, i + 1, &below);
+            }
+        }
+        let count = crossings(&layers, &below);
+        if count < best.0 {
+            best = (count, layers.clone());
+        }
+    }
+
+    let mut positions = vec![(0.0, 0.0); n];
+    for (y, layer) in best.1.iter().enumerate() {
+        let center = (layer.len() as f64 - 1.0) / 2.0;
+        for (x, node) in layer.iter().enumerate().filter(|(_, node)| **node < n) {
+            positions[*node] = (x as f64 - center, y as f64);
+        }
+    }
+
+    Ok(positions)
+}
+
+/// Sorts the layer `i` by the average position of the neighbors of its nodes
+/// in the `fixed` layer. Nodes without such neighbors keep their position.
+fn reorder(layers: &mut [Vec<usize>], i: usize, fixed: usize, links: &[Vec<usize>]) {
+    let mut rank = vec![0.0; links.len()];
+    for (position, node) in layers[fixed].iter().enumerate() {
+        rank[*node] = position as f64;
+    }
+
+    let mut keyed: Vec<(f64, usize)> = layers[i]
+        .iter()
+        .enumerate()
+        .map(|(position, node)| {
+            let linked = &links[*node];
+            if linked.is_empty() {
+                (position as f64, *node)
+            } else {
+                let sum: f64 = linked.iter().map(|l| rank[*l]).sum();
+                (sum / linked.len() as f64, *node)
+            }
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    layers[i] = keyed.into_iter().map(|(_, node)| node).collect();
+}
+
+/// The number of crossing pairs of edges between consecutive layers
+fn crossings(layers: &[Vec<usize>], below: &[Vec<usize>]) -> usize {
+    let mut rank = vec![0; below.len()];
+    for layer in layers {
+        for (position, node) in layer.iter().enumerate() {
+            rank[*node] = position;
+        }
+    }
+
+    layers
+        .iter()
+        .map(|layer| {
+            let edges: Vec<(usize, usize)> = layer
+                .iter()
+                .flat_map(|a| below[*a].iter().map(move |b| (*a, *b)))
+                .map(|(a, b)| (rank[a], rank[b]))
+                .collect();
+            edges
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| edges[i + 1..].iter().map(move |b| (a, b)))
+                .filter(|(a, b)| (a.0 < b.0 && a.1 > b.1) || (a.0 > b.0 && a.1 < b.1))
+                .count()
+        })
+        .sum()
+}
+
+/// The side of the square SVG canvas, in pixels
+const CANVAS: f64 = 600.0;
+const MARGIN: f64 = 30.0;
+const RADIUS: f64 = 14.0;
+
+/// Draws `graph` as an SVG image, placing nodes at `positions` scaled to fit the
+/// canvas. Nodes are circles labelled with their value. Edges are straight lines,
+/// with arrowheads on directed graphs. Self loops are not drawn.
+///
+/// Unlike the layouts, this takes a `Graph` since the labels come from node
+/// values, which the graph traits do not expose.
+///
+/// Panics if there is not exactly one position per node.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, layout::to_svg};
+/// let mut graph = Graph::new();
+/// let a = graph.add_node("a");
+/// let b = graph.add_node("b");
+/// graph.add_edge(a, b, 1);
+/// let svg = to_svg(&graph, &[(0.0, 0.0), (1.0, 1.0)]);
+/// assert!(svg.starts_with("<svg") && svg.contains(">b</text>"));
+/// ```
+pub fn to_svg<T: Display>(graph: &Graph<T>, positions: &[(f64, f64)]) -> String {
+    assert_eq!(
+        positions.len(),
+        graph.node_count(),
+        "Every node needs a position"
+    );
+
+    let bounds = |axis: fn(&(f64, f64)) -> f64| {
+        let min = positions.iter().map(axis).fold(f64::INFINITY, f64::min);
+        let max = positions.iter().map(axis).fold(f64::NEG_INFINITY, f64::max);
+        move |value: f64| {
+            if max > min {
+                MARGIN + (value - min) / (max - min) * (CANVAS - 2.0 * MARGIN)
+            } else {
+                CANVAS / 2.0
+            }
+        }
+    };
+    let (scale_x, scale_y) = (bounds(|p| p.0), bounds(|p| p.1));
+    let points: Vec<(f64, f64)> = positions
+        .iter()
+        .map(|(x, y)| (scale_x(*x), scale_y(*y)))
+        .collect();
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+        CANVAS
+    )
+    .unwrap();
+    if graph.is_directed() {
+        svg.push_str(concat!(
+            r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" "#,
+            r#"markerWidth="8" markerHeight="8" orient="auto">"#,
+            r#"<path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#,
+            "\n"
+        ));
+    }
+
+    for edge in graph.edges().filter(|e| e.from() != e.to()) {
+        // Lines stop at the border of the circles, so that arrowheads show
+        let (from, to) = (points[edge.from()], points[edge.to()]);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+        let (ux, uy) = (dx / length * RADIUS, dy / length * RADIUS);
+        write!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black""#,
+            from.0 + ux,
+            from.1 + uy,
+            to.0 - ux,
+            to.1 - uy
+        )
+        .unwrap();
+        if graph.is_directed() {
+            svg.push_str(r#" marker-end="url(#arrow)""#);
+        }
+        svg.push_str("/>\n");
+    }
+
+    for (node, (x, y)) in graph.nodes().zip(points) {
+        writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="white" stroke="black"/>"#,
+            x, y, RADIUS
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="central" font-size="12">{}</text>"#,
+            x,
+            y,
+            escape(&node.value().to_string())
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        offset(a, b).2
+    }
+
+    #[test]
+    fn force_directed_keeps_neighbors_close() {
+        let graph = generators::grid(4, 4);
+        let positions = fruchterman_reingold(&graph, 200, &mut StdRng::seed_from_u64(3));
+        assert!(positions
+            .iter()
+            .all(|(x, y)| (0.0..=1.0).contains(x) && (0.0..=1.0).contains(y)));
+
+        let (mut near, mut far) = (vec![], vec![]);
+        for a in 0..16 {
+            for b in a + 1..16 {
+                let d = distance(positions[a], positions[b]);
+                if graph.find_edge(a, b).is_some() {
+                    near.push(d);
+                } else {
+                    far.push(d);
+                }
+            }
+        }
+        let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+        assert!(mean(&near) * 2.0 < mean(&far));
+    }
+
+    #[test]
+    fn layers_remove_crossings() {
+        // Layers start in id order, where 0 -> 3 crosses 1 -> 2 and 3 -> 4
+        // crosses 2 -> 5
+        let mut graph = Graph::new();
+        for _ in 0..6 {
+            graph.add_node(());
+        }
+        for (a, b) in [(0, 3), (1, 2), (3, 4), (2, 5), (0, 5)].iter() {
+            graph.add_edge(*a, *b, 1);
+        }
+        let positions = sugiyama(&graph).unwrap();
+        let depths: Vec<f64> = positions.iter().map(|p| p.1).collect();
+        assert_eq!(depths, vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0]);
+
+        let x = |node: usize| positions[node].0;
+        assert_eq!(x(0) < x(1), x(3) < x(2));
+        assert_eq!(x(3) < x(2), x(4) < x(5));
+
+        graph.add_edge(5, 0, 1);
+        assert!(sugiyama(&graph).is_err());
+    }
+
+    #[test]
+    fn svg_output() {
+        let mut graph = Graph::new_undirected();
+        graph.add_node("<a>");
+        graph.add_node("b & c");
+        graph.add_node("d");
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 2, 1);
+
+        let svg = to_svg(&graph, &[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]);
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert!(!svg.contains("marker"));
+        assert!(svg.contains("&lt;a&gt;") && svg.contains("b &amp; c"));
+
+        let mut directed = Graph::new();
+        directed.add_node(1);
+        directed.add_node(2);
+        directed.add_edge(0, 1, 1);
+        let svg = to_svg(&directed, &sugiyama(&directed).unwrap());
+        assert!(svg.contains(r#"marker-end="url(#arrow)""#));
+    }
+}
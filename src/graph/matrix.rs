@@ -0,0 +1,324 @@
+use super::{Graph, NodeId};
+use crate::prelude::{EdgeRef, GraphBase, IntoEdges, IntoNeighbors, NodeCount, Visitable};
+
+/// A graph backed by a dense adjacency matrix. Edge lookups are O(1), at the
+/// cost of O(n²) memory, so it is best suited to small, dense graphs.
+///
+/// Being a matrix, it can hold at most one edge for each ordered pair of nodes.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::MatrixGraph;
+/// let mut graph = MatrixGraph::new();
+/// let a = graph.add_node("a");
+/// let b = graph.add_node("b");
+/// graph.add_edge(a, b, 3);
+/// assert!(graph.has_edge(a, b));
+/// assert_eq!(graph.edge_weight(a, b), Some(3));
+/// ```
+pub struct MatrixGraph<T = ()> {
+    nodes: Vec<T>,
+    weights: Vec<Option<usize>>,
+    edge_count: usize,
+    directed: bool,
+}
+
+impl<T> Default for MatrixGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MatrixGraph<T> {
+    /// Creates an empty directed matrix graph
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            weights: vec![],
+            edge_count: 0,
+            directed: true,
+        }
+    }
+
+    /// Creates an empty undirected matrix graph
+    pub fn new_undirected() -> Self {
+        Self {
+            directed: false,
+            ..Self::new()
+        }
+    }
+
+    /// Returns `true` if edges of this graph have a direction
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// The number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of edges in the graph
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Returns the value stored in node `id`, if any
+    pub fn node(&self, id: NodeId) -> Option<&T> {
+        self.nodes.get(id)
+    }
+
+    /// Adds a node holding `value` and returns its id. This grows the matrix,
+    /// so it costs O(n²).
+    pub fn add_node(&mut self, value: T) -> NodeId {
+        let n = self.nodes.len();
+        let mut weights = vec![None; (n + 1) * (n + 1)];
+        for row in 0..n {
+            weights[row * (n + 1)..row * (n + 1) + n]
+                .copy_from_slice(&self.weights[row * n..(row + 1) * n]);
+        }
+
+        self.weights = weights;
+        self.nodes.push(value);
+        n
+    }
+
+    /// Sets the weight of the edge between `from` and `to`, replacing the
+    /// previous one. Returns the old weight, if an edge was already there.
+    ///
+    /// Panics if any of the two nodes does not exist.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: usize) -> Option<usize> {
+        let old = self.set(from, to, Some(weight));
+        if old.is_none() {
+            self.edge_count += 1;
+        }
+
+        old
+    }
+
+    /// Removes the edge between `from` and `to`, returning its weight
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) -> Option<usize> {
+        let old = self.set(from, to, None);
+        if old.is_some() {
+            self.edge_count -= 1;
+        }
+
+        old
+    }
+
+    /// Returns `true` if there is an edge going from `from` to `to`
+    pub fn has_edge(&self, from: NodeId, to: NodeId) -> bool {
+        self.edge_weight(from, to).is_some()
+    }
+
+    /// The weight of the edge going from `from` to `to`, if any
+    pub fn edge_weight(&self, from: NodeId, to: NodeId) -> Option<usize> {
+        let n = self.nodes.len();
+        if from < n && to < n {
+            self.weights[from * n + to]
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the nodes reachable from `id` through a single edge
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let n = self.nodes.len();
+        self.weights[id * n..(id + 1) * n]
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.is_some())
+            .map(|(to, _)| to)
+    }
+
+    fn set(&mut self, from: NodeId, to: NodeId, weight: Option<usize>) -> Option<usize> {
+        let n = self.nodes.len();
+        assert!(
+            from < n && to < n,
+            "Cannot add an edge between nodes that do not exist"
+        );
+
+        let old = std::mem::replace(&mut self.weights[from * n + to], weight);
+        if !self.directed {
+            self.weights[to * n + from] = weight;
+        }
+
+        old
+    }
+}
+
+impl<T> GraphBase for MatrixGraph<T> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<T> NodeCount for MatrixGraph<T> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<T> IntoNeighbors for MatrixGraph<T> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(MatrixGraph::neighbors(self, node))
+    }
+}
+
+/// Edge ids of a matrix graph are the positions of the edges inside the matrix.
+/// Undirected edges use the position above the diagonal, so that both of their
+/// endpoints report the same id.
+impl<T> IntoEdges for MatrixGraph<T> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        let n = self.nodes.len();
+        Box::new(MatrixGraph::neighbors(self, node).map(move |target| {
+            let (row, column) = if self.directed {
+                (node, target)
+            } else {
+                (node.min(target), node.max(target))
+            };
+            EdgeRef {
+                id: row * n + column,
+                source: node,
+                target,
+                weight: self.weights[node * n + target].unwrap(),
+            }
+        }))
+    }
+}
+
+impl<T> Visitable for MatrixGraph<T> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        vec![false; self.nodes.len()]
+    }
+}
+
+/// Converts a `Graph` into its matrix form. Node ids are preserved. When the
+/// graph has parallel edges, only the lightest one is kept.
+impl<T> From<Graph<T>> for MatrixGraph<T> {
+    fn from(graph: Graph<T>) -> Self {
+        let Graph {
+            nodes,
+            edges,
+            directed,
+            ..
+        } = graph;
+        let n = nodes.len();
+        let mut matrix = MatrixGraph {
+            nodes: nodes.into_iter().map(|node| node.value).collect(),
+            weights: vec![None; n * n],
+            edge_count: 0,
+            directed,
+        };
+
+        for edge in edges {
+            match matrix.edge_weight(edge.from(), edge.to()) {
+                Some(w) if w <= edge.weight() => (),
+                _ => {
+                    matrix.add_edge(edge.from(), edge.to(), edge.weight());
+                }
+            }
+        }
+
+        matrix
+    }
+}
+
+/// Converts a matrix graph into an adjacency list `Graph`. Node ids are preserved.
+impl<T> From<MatrixGraph<T>> for Graph<T> {
+    fn from(matrix: MatrixGraph<T>) -> Self {
+        let mut graph = if matrix.directed {
+            Graph::new()
+        } else {
+            Graph::new_undirected()
+        };
+
+        let n = matrix.nodes.len();
+        for value in matrix.nodes {
+            graph.add_node(value);
+        }
+
+        for from in 0..n {
+            let first = if matrix.directed { 0 } else { from };
+            for to in first..n {
+                if let Some(weight) = matrix.weights[from * n + to] {
+                    graph.add_edge(from, to, weight);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Node;
+
+    #[test]
+    fn add_node_keeps_edges() {
+        let mut graph = MatrixGraph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, 2);
+        let c = graph.add_node(());
+        graph.add_edge(c, a, 4);
+        assert_eq!(graph.edge_weight(a, b), Some(2));
+        assert_eq!(graph.edge_weight(c, a), Some(4));
+        assert!(!graph.has_edge(b, a));
+        assert_eq!(graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn undirected_symmetry() {
+        let mut graph = MatrixGraph::new_undirected();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, 1);
+        assert!(graph.has_edge(b, a));
+        assert_eq!(graph.remove_edge(b, a), Some(1));
+        assert!(!graph.has_edge(a, b));
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn undirected_edge_ids() {
+        let mut graph = MatrixGraph::new_undirected();
+        for _ in 0..3 {
+            graph.add_node(());
+        }
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(2, 1, 1);
+        let id = |from, to| {
+            IntoEdges::edges(&graph, from)
+                .find(|e| e.target == to)
+                .unwrap()
+                .id
+        };
+        assert_eq!(id(0, 1), id(1, 0));
+        assert_eq!(id(1, 2), id(2, 1));
+        assert_ne!(id(0, 1), id(1, 2));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_edge(a, b, 7);
+        graph.add_edge(a, b, 3);
+        graph.add_edge(b, a, 1);
+
+        let matrix = MatrixGraph::from(graph);
+        assert_eq!(matrix.edge_count(), 2);
+        assert_eq!(matrix.edge_weight(a, b), Some(3));
+
+        let graph = Graph::from(matrix);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(*graph.node(b).unwrap().value(), "b");
+        assert_eq!(graph.neighbors(a).collect::<Vec<NodeId>>(), vec![b]);
+    }
+}
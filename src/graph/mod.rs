@@ -0,0 +1,446 @@
+use std::ops::Deref;
+
+use crate::prelude::{EdgeRef, GraphBase, IntoEdges, IntoNeighbors, Node, NodeCount, Visitable};
+
+mod dag;
+pub use dag::*;
+mod diff;
+pub use diff::*;
+mod dynamic;
+pub use dynamic::*;
+mod hypergraph;
+pub use hypergraph::*;
+mod matrix;
+pub use matrix::*;
+
+mod operations;
+mod view;
+pub use view::*;
+
+pub mod algo;
+pub mod centrality;
+pub mod cliques;
+pub mod coloring;
+pub mod community;
+pub mod connectivity;
+pub mod dominators;
+pub mod generators;
+pub mod hamiltonian;
+pub mod isomorphism;
+pub mod layout;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod planarity;
+pub mod reachability;
+pub mod tsp;
+
+/// The index of a node inside a graph
+pub type NodeId = usize;
+
+/// The index of an edge inside a graph
+pub type EdgeId = usize;
+
+/// The edges a `Graph` accepts, enforced by `add_edge`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgePolicy {
+    /// Whether several edges may join the same two nodes (in the same direction,
+    /// on directed graphs)
+    pub parallel_edges: bool,
+    /// Whether an edge may join a node to itself
+    pub self_loops: bool,
+}
+
+impl EdgePolicy {
+    /// Accepts every edge
+    pub const MULTIGRAPH: EdgePolicy = EdgePolicy {
+        parallel_edges: true,
+        self_loops: true,
+    };
+
+    /// Accepts at most one edge between two nodes, and none from a node to itself
+    pub const SIMPLE: EdgePolicy = EdgePolicy {
+        parallel_edges: false,
+        self_loops: false,
+    };
+}
+
+impl Default for EdgePolicy {
+    fn default() -> Self {
+        Self::MULTIGRAPH
+    }
+}
+
+/// A graph stored as adjacency lists. Nodes and edges are addressed by their
+/// insertion index, so ids stay valid for the whole life of the graph.
+///
+/// Graphs accept parallel edges and self loops unless given a stricter
+/// `EdgePolicy`. Algorithms handle parallel edges as distinct edges: flows add
+/// up their capacities, shortest paths take the lightest one, and so on. Each
+/// module documents the cases where this matters.
+pub struct Graph<T = ()> {
+    nodes: Vec<GraphNode<T>>,
+    edges: Vec<Edge>,
+    directed: bool,
+    policy: EdgePolicy,
+}
+
+impl<T> Default for Graph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Graph<T> {
+    /// Creates an empty directed graph
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            edges: vec![],
+            directed: true,
+            policy: EdgePolicy::default(),
+        }
+    }
+
+    /// Creates an empty undirected graph
+    pub fn new_undirected() -> Self {
+        Self {
+            directed: false,
+            ..Self::new()
+        }
+    }
+
+    /// Returns `true` if edges of this graph have a direction
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Sets the edges this graph accepts
+    ///
+    /// Panics if the graph already has edges.
+    ///
+    /// ## Example
+    /// ```
+    /// # use fluffy_structs::{EdgePolicy, Graph};
+    /// let mut graph = Graph::new_undirected().with_policy(EdgePolicy::SIMPLE);
+    /// let a = graph.add_node(());
+    /// let b = graph.add_node(());
+    /// let ab = graph.add_edge(a, b, 1);
+    /// assert_eq!(graph.update_edge(b, a, 5), ab);
+    /// assert_eq!(graph.edge(ab).unwrap().weight(), 5);
+    /// ```
+    pub fn with_policy(mut self, policy: EdgePolicy) -> Self {
+        assert!(
+            self.edges.is_empty(),
+            "Cannot change the policy of a graph with edges"
+        );
+        self.policy = policy;
+        self
+    }
+
+    /// The edges this graph accepts
+    pub fn policy(&self) -> EdgePolicy {
+        self.policy
+    }
+
+    pub fn spawn(&mut self, value: T) -> &GraphNode<T> {
+        let id = self.add_node(value);
+        self.nodes.get(id).unwrap()
+    }
+
+    /// Adds a node holding `value` and returns its id
+    pub fn add_node(&mut self, value: T) -> NodeId {
+        let id = self.nodes.len();
+        let node = GraphNode {
+            id,
+            value,
+            edges: vec![],
+            incoming: vec![],
+        };
+
+        self.nodes.push(node);
+        id
+    }
+
+    /// Adds an edge going from `from` to `to` and returns its id. On undirected
+    /// graphs the edge is reachable from both of its endpoints.
+    ///
+    /// Panics if any of the two nodes does not exist, or if the policy of the
+    /// graph forbids the edge.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: usize) -> EdgeId {
+        assert!(
+            from < self.nodes.len() && to < self.nodes.len(),
+            "Cannot add an edge between nodes that do not exist"
+        );
+        assert!(
+            self.policy.self_loops || from != to,
+            "This graph does not accept self loops"
+        );
+        assert!(
+            self.policy.parallel_edges || self.find_edge(from, to).is_none(),
+            "This graph does not accept parallel edges"
+        );
+
+        let id = self.edges.len();
+        self.edges.push(Edge { from, to, weight });
+        self.nodes[from].edges.push(id);
+        if self.directed {
+            self.nodes[to].incoming.push(id);
+        } else if from != to {
+            self.nodes[to].edges.push(id);
+        }
+
+        id
+    }
+
+    /// Sets the weight of the edge going from `from` to `to`, adding it if there
+    /// is none, and returns its id. When there are several such edges, the first
+    /// one is updated.
+    ///
+    /// Panics if any of the two nodes does not exist, or if the edge has to be
+    /// added and the policy of the graph forbids it.
+    pub fn update_edge(&mut self, from: NodeId, to: NodeId, weight: usize) -> EdgeId {
+        match self.find_edge(from, to) {
+            Some(id) => {
+                self.edges[id].weight = weight;
+                id
+            }
+            None => self.add_edge(from, to, weight),
+        }
+    }
+
+    /// The number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of edges in the graph
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns the node with id `id`, if any
+    pub fn node(&self, id: NodeId) -> Option<&GraphNode<T>> {
+        self.nodes.get(id)
+    }
+
+    /// Returns the edge with id `id`, if any
+    pub fn edge(&self, id: EdgeId) -> Option<&Edge> {
+        self.edges.get(id)
+    }
+
+    /// Returns an iterator over all the nodes, in id order
+    pub fn nodes(&self) -> std::slice::Iter<'_, GraphNode<T>> {
+        self.nodes.iter()
+    }
+
+    /// Returns an iterator over all the edges, in id order
+    pub fn edges(&self) -> std::slice::Iter<'_, Edge> {
+        self.edges.iter()
+    }
+
+    /// Returns an iterator over the edges leaving `id` (or touching it, if the
+    /// graph is undirected)
+    pub fn edges_of(&self, id: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.nodes[id].edges.iter().map(move |e| &self.edges[*e])
+    }
+
+    /// Returns an iterator over the nodes reachable from `id` through a single edge
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges_of(id).map(move |e| e.other(id))
+    }
+
+    /// Returns the id of an edge going from `from` to `to`, if any
+    pub fn find_edge(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        self.nodes[from]
+            .edges
+            .iter()
+            .copied()
+            .find(|e| self.edges[*e].other(from) == to)
+    }
+}
+
+impl<T> GraphBase for Graph<T> {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl<T> NodeCount for Graph<T> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<T> IntoNeighbors for Graph<T> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(Graph::neighbors(self, node))
+    }
+}
+
+impl<T> IntoEdges for Graph<T> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        Box::new(self.nodes[node].edges.iter().map(move |&id| {
+            let edge = &self.edges[id];
+            EdgeRef {
+                id,
+                source: node,
+                target: edge.other(node),
+                weight: edge.weight,
+            }
+        }))
+    }
+}
+
+impl<T> Visitable for Graph<T> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        vec![false; self.nodes.len()]
+    }
+}
+
+/// A weighted connection between two nodes of a graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    from: NodeId,
+    to: NodeId,
+    weight: usize,
+}
+
+impl Edge {
+    /// The node this edge starts from
+    pub fn from(&self) -> NodeId {
+        self.from
+    }
+
+    /// The node this edge points to
+    pub fn to(&self) -> NodeId {
+        self.to
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Given one endpoint of this edge, returns the other one
+    pub fn other(&self, node: NodeId) -> NodeId {
+        if node == self.from {
+            self.to
+        } else {
+            self.from
+        }
+    }
+}
+
+pub struct GraphNode<T> {
+    id: NodeId,
+    value: T,
+    edges: Vec<EdgeId>,
+    incoming: Vec<EdgeId>,
+}
+
+impl<T> Node for GraphNode<T> {
+    type Output = T;
+
+    fn value(&self) -> &Self::Output {
+        &self.value
+    }
+}
+
+impl<T> Deref for GraphNode<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> GraphNode<T> {
+    /// The id of this node inside its graph
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// The ids of the edges leaving this node (or touching it, if the graph is undirected)
+    pub fn edges(&self) -> &[EdgeId] {
+        &self.edges
+    }
+
+    /// The ids of the edges pointing to this node. Always empty on undirected graphs.
+    pub fn incoming(&self) -> &[EdgeId] {
+        &self.incoming
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_test_graph() -> Graph<&'static str> {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, c, 2);
+        graph.add_edge(a, c, 5);
+
+        graph
+    }
+
+    #[test]
+    fn add_edges() {
+        let graph = make_test_graph();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.neighbors(0).collect::<Vec<NodeId>>(), vec![1, 2]);
+        assert!(graph.neighbors(2).next().is_none());
+        assert_eq!(graph.node(2).unwrap().incoming(), &[1, 2]);
+    }
+
+    #[test]
+    fn undirected_neighbors() {
+        let mut graph = Graph::new_undirected();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, 1);
+        assert_eq!(graph.neighbors(b).collect::<Vec<NodeId>>(), vec![a]);
+        assert_eq!(graph.find_edge(b, a), Some(0));
+    }
+
+    #[test]
+    fn simple_policy() {
+        let mut graph = Graph::new().with_policy(EdgePolicy::SIMPLE);
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, a, 1);
+        assert_eq!(graph.update_edge(a, b, 3), 0);
+        assert_eq!(graph.edge(0).unwrap().weight(), 3);
+        assert_eq!(graph.update_edge(a, c, 4), 2);
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "parallel edges")]
+    fn parallel_edges_are_rejected() {
+        let mut graph = Graph::new_undirected().with_policy(EdgePolicy {
+            parallel_edges: false,
+            self_loops: true,
+        });
+        graph.add_node(());
+        graph.add_node(());
+        graph.add_edge(0, 0, 1);
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 0, 1);
+    }
+
+    #[test]
+    fn find_edge() {
+        let graph = make_test_graph();
+        assert_eq!(graph.find_edge(1, 2), Some(1));
+        assert_eq!(graph.find_edge(2, 1), None);
+        assert_eq!(graph.edge(2).unwrap().weight(), 5);
+    }
+}
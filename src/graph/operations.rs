@@ -0,0 +1,364 @@
+//! Operations building a new graph out of existing ones. Besides the graph, each
+//! of them returns where the nodes (or edges) of its inputs ended up.
+//!
+//! New graphs take the edge policy of `self`. Edges it forbids are merged
+//! instead: self loops are dropped and parallel edges keep the lightest weight.
+
+use super::{EdgeId, Graph, NodeId};
+
+impl<T> Graph<T> {
+    /// An empty graph with the same directedness and policy as this one
+    pub(super) fn empty<U>(&self) -> Graph<U> {
+        Graph {
+            directed: self.directed,
+            policy: self.policy,
+            ..Graph::new()
+        }
+    }
+
+    /// Adds an edge if the policy allows it, or merges it into the graph otherwise
+    fn merge_edge(&mut self, from: NodeId, to: NodeId, weight: usize) {
+        if from == to && !self.policy.self_loops {
+            return;
+        }
+        match self
+            .find_edge(from, to)
+            .filter(|_| !self.policy.parallel_edges)
+        {
+            Some(id) => self.edges[id].weight = self.edges[id].weight.min(weight),
+            None => {
+                self.add_edge(from, to, weight);
+            }
+        }
+    }
+
+    /// Builds the graph with the same nodes and an edge of weight 1 wherever this
+    /// one has none, self loops excepted. Returns it with the new id of every node,
+    /// which is the same as the old one.
+    pub fn complement(&self) -> (Graph<T>, Vec<NodeId>)
+    where
+        T: Clone,
+    {
+        let mut complement = self.empty();
+        for node in &self.nodes {
+            complement.add_node(node.value.clone());
+        }
+        for a in 0..self.node_count() {
+            for b in 0..self.node_count() {
+                let wanted = a != b && (self.directed || a < b);
+                if wanted && self.find_edge(a, b).is_none() {
+                    complement.merge_edge(a, b, 1);
+                }
+            }
+        }
+
+        (complement, (0..self.node_count()).collect())
+    }
+
+    /// Builds a graph holding a copy of this graph and a copy of `other`, side by
+    /// side. Returns it with the new ids of the nodes of both graphs.
+    ///
+    /// Panics if only one of the two graphs is directed.
+    pub fn disjoint_union(&self, other: &Graph<T>) -> (Graph<T>, Vec<NodeId>, Vec<NodeId>)
+    where
+        T: Clone,
+    {
+        assert_eq!(
+            self.directed, other.directed,
+            "Cannot mix directed and undirected graphs"
+        );
+
+        let mut union = self.empty();
+        let mut mappings = vec![];
+        for graph in [self, other].iter() {
+            let mapping: Vec<NodeId> = graph
+                .nodes
+                .iter()
+                .map(|node| union.add_node(node.value.clone()))
+                .collect();
+            for edge in &graph.edges {
+                union.merge_edge(mapping[edge.from], mapping[edge.to], edge.weight);
+            }
+            mappings.push(mapping);
+        }
+
+        let second = mappings.pop().unwrap();
+        (union, mappings.pop().unwrap(), second)
+    }
+
+    /// Builds the cartesian product of this graph and `other`: its nodes are the
+    /// pairs `(a, b)`, and there is an edge from `(a, b)` to `(c, d)` when `a = c`
+    /// and `b` is linked to `d`, or when `b = d` and `a` is linked to `c`. Edges
+    /// keep the weight of the edge they come from. Returns it with the id of every
+    /// pair, as `mapping[a][b]`.
+    ///
+    /// Panics if only one of the two graphs is directed.
+    ///
+    /// ## Example
+    /// ```
+    /// # use fluffy_structs::generators;
+    /// // The product of two paths is a grid
+    /// let (grid, _) = generators::path(3).cartesian_product(&generators::path(4));
+    /// assert_eq!((grid.node_count(), grid.edge_count()), (12, 17));
+    /// ```
+    pub fn cartesian_product<U: Clone>(&self, other: &Graph<U>) -> (Graph<(T, U)>, Vec<Vec<NodeId>>)
+    where
+        T: Clone,
+    {
+        let (mut product, mapping) = self.product_nodes(other);
+        for edge in &self.edges {
+            let (from, to) = (&mapping[edge.from], &mapping[edge.to]);
+            for (a, b) in from.iter().zip(to) {
+                product.merge_edge(*a, *b, edge.weight);
+            }
+        }
+        for edge in &other.edges {
+            for row in &mapping {
+                product.merge_edge(row[edge.from], row[edge.to], edge.weight);
+            }
+        }
+
+        (product, mapping)
+    }
+
+    /// Builds the tensor product of this graph and `other`: its nodes are the
+    /// pairs `(a, b)`, and there is an edge from `(a, b)` to `(c, d)` when `a` is
+    /// linked to `c` and `b` to `d`. Each edge weighs the product of the weights of
+    /// the two edges it comes from. Returns it with the id of every pair, as
+    /// `mapping[a][b]`.
+    ///
+    /// Panics if only one of the two graphs is directed.
+    pub fn tensor_product<U: Clone>(&self, other: &Graph<U>) -> (Graph<(T, U)>, Vec<Vec<NodeId>>)
+    where
+        T: Clone,
+    {
+        let (mut product, mapping) = self.product_nodes(other);
+        for x in &self.edges {
+            for y in &other.edges {
+                let weight = x.weight * y.weight;
+                product.merge_edge(mapping[x.from][y.from], mapping[x.to][y.to], weight);
+                // Undirected edges also pair up crosswise, unless one of them is a
+                // self loop, where both pairings give the same edge
+                if !self.directed && x.from != x.to && y.from != y.to {
+                    product.merge_edge(mapping[x.from][y.to], mapping[x.to][y.from], weight);
+                }
+            }
+        }
+
+        (product, mapping)
+    }
+
+    fn product_nodes<U: Clone>(&self, other: &Graph<U>) -> (Graph<(T, U)>, Vec<Vec<NodeId>>)
+    where
+        T: Clone,
+    {
+        assert_eq!(
+            self.directed, other.directed,
+            "Cannot mix directed and undirected graphs"
+        );
+
+        let mut product = self.empty();
+        let mapping = self
+            .nodes
+            .iter()
+            .map(|a| {
+                other
+                    .nodes
+                    .iter()
+                    .map(|b| product.add_node((a.value.clone(), b.value.clone())))
+                    .collect()
+            })
+            .collect();
+
+        (product, mapping)
+    }
+
+    /// Builds the line graph, which has a node for every edge of this graph,
+    /// holding its id. Two of them are linked by an edge of weight 1 when their
+    /// edges share an endpoint. On directed graphs, the first edge must instead
+    /// end where the second starts. Returns it with the new node of every edge,
+    /// which has the same id.
+    pub fn line_graph(&self) -> (Graph<EdgeId>, Vec<NodeId>) {
+        let mut line = self.empty();
+        for id in 0..self.edge_count() {
+            line.add_node(id);
+        }
+
+        for (id, edge) in self.edges.iter().enumerate() {
+            let mut next: Vec<EdgeId> = if self.directed {
+                self.nodes[edge.to].edges.clone()
+            } else {
+                let mut touching = self.nodes[edge.from].edges.clone();
+                touching.extend(&self.nodes[edge.to].edges);
+                touching.retain(|other| *other > id);
+                touching
+            };
+            next.sort_unstable();
+            next.dedup();
+            for other in next.into_iter().filter(|other| *other != id) {
+                line.merge_edge(id, other, 1);
+            }
+        }
+
+        (line, (0..self.edge_count()).collect())
+    }
+
+    /// Builds the subgraph made of `nodes` and of the edges between them. Returns
+    /// it with the new id of every node, `None` for the ones left out.
+    ///
+    /// Panics if a node does not exist.
+    pub fn induced_subgraph(&self, nodes: &[NodeId]) -> (Graph<T>, Vec<Option<NodeId>>)
+    where
+        T: Clone,
+    {
+        let mut subgraph = self.empty();
+        let mut mapping = vec![None; self.node_count()];
+        for &node in nodes {
+            if mapping[node].is_none() {
+                mapping[node] = Some(subgraph.add_node(self.nodes[node].value.clone()));
+            }
+        }
+        for edge in &self.edges {
+            if let (Some(from), Some(to)) = (mapping[edge.from], mapping[edge.to]) {
+                subgraph.merge_edge(from, to, edge.weight);
+            }
+        }
+
+        (subgraph, mapping)
+    }
+
+    /// Builds the graph where the two endpoints of edge `e` are merged into a
+    /// single node, holding `merge` of their values and taking the place of the
+    /// endpoint with the smaller id. The edge itself disappears, while any other
+    /// edge between the two endpoints becomes a self loop, if the policy allows
+    /// it. Returns the graph with the new id of every node.
+    ///
+    /// Panics if the edge does not exist.
+    ///
+    /// ## Example
+    /// ```
+    /// # use fluffy_structs::{prelude::Node, Graph};
+    /// let mut graph = Graph::new_undirected();
+    /// let a = graph.add_node(1);
+    /// let b = graph.add_node(2);
+    /// let c = graph.add_node(3);
+    /// let ab = graph.add_edge(a, b, 1);
+    /// graph.add_edge(b, c, 1);
+    ///
+    /// let (contracted, mapping) = graph.contract_edge(ab, |x, y| x + y);
+    /// assert_eq!(mapping, vec![0, 0, 1]);
+    /// assert_eq!(*contracted.node(0).unwrap().value(), 3);
+    /// ```
+    pub fn contract_edge<F>(&self, e: EdgeId, merge: F) -> (Graph<T>, Vec<NodeId>)
+    where
+        T: Clone,
+        F: FnOnce(&T, &T) -> T,
+    {
+        let edge = self.edges[e];
+        let (kept, removed) = (edge.from.min(edge.to), edge.from.max(edge.to));
+
+        let mut contracted = self.empty();
+        let mut mapping = vec![0; self.node_count()];
+        let mut merge = Some(merge);
+        for (id, node) in self.nodes.iter().enumerate() {
+            if id == removed {
+                continue;
+            }
+            let value = match merge.take().filter(|_| id == kept) {
+                Some(merge) => merge(&self.nodes[kept].value, &self.nodes[removed].value),
+                None => node.value.clone(),
+            };
+            mapping[id] = contracted.add_node(value);
+        }
+        mapping[removed] = mapping[kept];
+
+        for (id, edge) in self.edges.iter().enumerate() {
+            if id != e {
+                contracted.merge_edge(mapping[edge.from], mapping[edge.to], edge.weight);
+            }
+        }
+
+        (contracted, mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generators, prelude::Node, EdgePolicy, Graph};
+
+    #[test]
+    fn complement_and_union() {
+        let (complement, _) = generators::petersen().complement();
+        assert_eq!(complement.edge_count(), 45 - 15);
+
+        let mut directed = Graph::new();
+        directed.add_node('a');
+        directed.add_node('b');
+        directed.add_edge(0, 1, 1);
+        let (complement, _) = directed.complement();
+        assert_eq!(complement.find_edge(1, 0), Some(0));
+        assert_eq!(complement.edge_count(), 1);
+
+        let (union, left, right) = directed.disjoint_union(&complement);
+        assert_eq!((left, right.clone()), (vec![0, 1], vec![2, 3]));
+        assert!(union.find_edge(right[1], right[0]).is_some());
+        assert_eq!(*union.node(3).unwrap().value(), 'b');
+    }
+
+    #[test]
+    fn products() {
+        let (cube, mapping) = generators::hypercube(2).cartesian_product(&generators::path(2));
+        assert_eq!((cube.node_count(), cube.edge_count()), (8, 12));
+        assert!(cube.find_edge(mapping[3][0], mapping[3][1]).is_some());
+
+        // K₂ × K₃ tensor product is a 6-cycle
+        let (tensor, _) = generators::complete(2).tensor_product(&generators::complete(3));
+        assert_eq!(tensor.edge_count(), 6);
+        assert!((0..6).all(|n| tensor.neighbors(n).count() == 2));
+    }
+
+    #[test]
+    fn line_graphs() {
+        // The line graph of a star is complete
+        let (line, _) = generators::star(5).line_graph();
+        assert_eq!((line.node_count(), line.edge_count()), (4, 6));
+
+        let mut directed = Graph::new();
+        for _ in 0..3 {
+            directed.add_node(());
+        }
+        directed.add_edge(0, 1, 1);
+        directed.add_edge(1, 2, 1);
+        directed.add_edge(2, 0, 1);
+        let (line, _) = directed.line_graph();
+        let edges: Vec<_> = line.edges().map(|e| (e.from(), e.to())).collect();
+        assert_eq!(edges, vec![(0, 1), (1, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn subgraphs_and_contractions() {
+        let (subgraph, mapping) = generators::wheel(6).induced_subgraph(&[0, 2, 3]);
+        assert_eq!(mapping, vec![Some(0), None, Some(1), Some(2), None, None]);
+        assert_eq!(subgraph.edge_count(), 3);
+
+        let mut graph = generators::cycle(4);
+        graph.add_edge(0, 1, 7);
+        let (contracted, mapping) = graph.contract_edge(0, |_, _| ());
+        assert_eq!(mapping, vec![0, 0, 1, 2]);
+        assert_eq!(contracted.edge_count(), 4);
+        assert_eq!(contracted.find_edge(0, 0), Some(3));
+
+        // Under the simple policy, the loop goes away and the two edges going
+        // to the merged node become one
+        let mut triangle = Graph::new_undirected().with_policy(EdgePolicy::SIMPLE);
+        for _ in 0..3 {
+            triangle.add_node(());
+        }
+        triangle.add_edge(0, 1, 1);
+        triangle.add_edge(1, 2, 4);
+        triangle.add_edge(2, 0, 2);
+        let (contracted, _) = triangle.contract_edge(0, |_, _| ());
+        assert_eq!(contracted.edge_count(), 1);
+        assert_eq!(contracted.edge(0).unwrap().weight(), 2);
+    }
+}
@@ -0,0 +1,323 @@
+//! Multi-threaded versions of some graph algorithms, enabled by the `parallel`
+//! feature and running on the rayon thread pool. Each function returns exactly
+//! what its sequential counterpart does, floating point rounding included, so
+//! the two can be swapped freely.
+//!
+//! These algorithms run on a `Csr`, a compact read-only copy of the adjacency
+//! lists of any graph, which threads scan as plain slices instead of going
+//! through boxed iterators. Building one takes linear time and pays off when it
+//! is reused.
+//!
+//! ## Example
+//! ```
+//! # use fluffy_structs::{algo, generators, parallel::{self, Csr}};
+//! let graph = generators::grid(30, 30);
+//! let csr = Csr::new(&graph);
+//! assert_eq!(parallel::bfs(&csr, 0), algo::Bfs::new(&graph, 0).collect::<Vec<_>>());
+//! assert_eq!(parallel::connected_components(&csr), algo::connected_components(&graph));
+//! ```
+
+use rayon::prelude::*;
+
+use super::{centrality::NotConverged, NodeId};
+use crate::prelude::{GraphBase, IntoNeighbors, NodeCount, Visitable};
+
+/// A graph stored in compressed sparse row form: the neighbors of all nodes are
+/// packed in a single vector, in the order the original graph listed them.
+/// Incoming edges are stored the same way, and nodes hidden in the original
+/// graph stay hidden.
+pub struct Csr {
+    directed: bool,
+    visible: Vec<bool>,
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    in_offsets: Vec<usize>,
+    sources: Vec<NodeId>,
+}
+
+impl Csr {
+    /// Copies the adjacency lists of `graph`
+    pub fn new<G: IntoNeighbors + NodeCount>(graph: &G) -> Self {
+        let n = graph.node_count();
+        let mut offsets = Vec::with_capacity(n + 1);
+        let mut targets = vec![];
+        offsets.push(0);
+        for node in 0..n {
+            targets.extend(graph.neighbors(node));
+            offsets.push(targets.len());
+        }
+
+        // Counting sort by target, so the sources of every node come in order
+        let mut in_offsets = vec![0; n + 1];
+        for &target in &targets {
+            in_offsets[target + 1] += 1;
+        }
+        for node in 0..n {
+            in_offsets[node + 1] += in_offsets[node];
+        }
+        let mut next = in_offsets.clone();
+        let mut sources = vec![0; targets.len()];
+        for node in 0..n {
+            for &target in &targets[offsets[node]..offsets[node + 1]] {
+                sources[next[target]] = node;
+                next[target] += 1;
+            }
+        }
+
+        Self {
+            directed: graph.is_directed(),
+            visible: (0..n).map(|node| graph.contains_node(node)).collect(),
+            offsets,
+            targets,
+            in_offsets,
+            sources,
+        }
+    }
+
+    /// The number of adjacency list entries, which is the number of edges on
+    /// directed graphs and twice that (less self loops) on undirected ones
+    pub fn entry_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// The nodes reachable from `node` through a single edge
+    pub fn outgoing(&self, node: NodeId) -> &[NodeId] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// The nodes with an edge to `node`, sorted
+    pub fn incoming(&self, node: NodeId) -> &[NodeId] {
+        &self.sources[self.in_offsets[node]..self.in_offsets[node + 1]]
+    }
+}
+
+impl GraphBase for Csr {
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+}
+
+impl NodeCount for Csr {
+    fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn contains_node(&self, node: NodeId) -> bool {
+        self.visible[node]
+    }
+}
+
+impl IntoNeighbors for Csr {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(self.outgoing(node).iter().copied())
+    }
+}
+
+impl Visitable for Csr {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        vec![false; self.node_count()]
+    }
+}
+
+/// The nodes reachable from `start`, in the order `algo::Bfs` visits them. Each
+/// level of the search scans the neighbors of its nodes in parallel.
+pub fn bfs(graph: &Csr, start: NodeId) -> Vec<NodeId> {
+    let mut visited = vec![false; graph.node_count()];
+    visited[start] = true;
+    let mut order = vec![start];
+    let mut level = 0;
+    while level < order.len() {
+        let candidates: Vec<NodeId> = order[level..]
+            .par_iter()
+            .flat_map_iter(|&node| graph.outgoing(node).iter().copied())
+            .filter(|&next| !visited[next])
+            .collect();
+
+        level = order.len();
+        for next in candidates {
+            if !std::mem::replace(&mut visited[next], true) {
+                order.push(next);
+            }
+        }
+    }
+
+    order
+}
+
+/// The same ranks as `centrality::pagerank`. Every node gathers the rank of its
+/// incoming neighbors in parallel, adding them up in the sequential order.
+pub fn pagerank(
+    graph: &Csr,
+    damping: f64,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<Vec<f64>, NotConverged> {
+    let n = graph.node_count();
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    if nodes.is_empty() {
+        return Ok(vec![0.0; n]);
+    }
+
+    let count = nodes.len() as f64;
+    let out_degrees: Vec<usize> = (0..n).map(|node| graph.outgoing(node).len()).collect();
+    let mut ranks = vec![0.0; n];
+    for &node in &nodes {
+        ranks[node] = 1.0 / count;
+    }
+    for _ in 0..max_iterations {
+        let dangling: f64 = nodes
+            .iter()
+            .filter(|node| out_degrees[**node] == 0)
+            .map(|node| ranks[*node])
+            .sum();
+        let base = (1.0 - damping) / count + damping * dangling / count;
+        let shares: Vec<f64> = (0..n)
+            .into_par_iter()
+            .map(|node| match out_degrees[node] {
+                0 => 0.0,
+                degree => damping * ranks[node] / degree as f64,
+            })
+            .collect();
+        let next: Vec<f64> = (0..n)
+            .into_par_iter()
+            .map(|node| {
+                if !graph.contains_node(node) {
+                    return 0.0;
+                }
+                graph
+                    .incoming(node)
+                    .iter()
+                    .fold(base, |rank, source| rank + shares[*source])
+            })
+            .collect();
+
+        let change: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if change < tolerance {
+            return Ok(ranks);
+        }
+    }
+
+    Err(NotConverged(ranks))
+}
+
+/// The same labels as `algo::connected_components`, found by label propagation:
+/// every node repeatedly takes the smallest label among its neighbors and then
+/// the label of the node it points to, until nothing changes.
+pub fn connected_components(graph: &Csr) -> Vec<usize> {
+    let n = graph.node_count();
+    let mut labels: Vec<NodeId> = (0..n).collect();
+    loop {
+        let propagated: Vec<NodeId> = (0..n)
+            .into_par_iter()
+            .map(|node| {
+                let neighbors = graph.outgoing(node).iter().chain(graph.incoming(node));
+                neighbors
+                    .map(|next| labels[*next])
+                    .fold(labels[node], usize::min)
+            })
+            .collect();
+        // A label is a node of the same component, whose own label is no larger
+        let next: Vec<NodeId> = (0..n)
+            .into_par_iter()
+            .map(|node| propagated[propagated[node]])
+            .collect();
+
+        if next == labels {
+            break;
+        }
+        labels = next;
+    }
+
+    // Every node is now labeled with the smallest node of its component, and
+    // hidden nodes, which have no neighbors, with themselves
+    let mut numbers = vec![usize::MAX; n];
+    let mut count = 0;
+    for node in graph.node_ids() {
+        if labels[node] == node {
+            numbers[node] = count;
+            count += 1;
+        }
+    }
+    labels.into_iter().map(|root| numbers[root]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algo, centrality, generators, Graph};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_directed(n: usize, m: usize, rng: &mut StdRng) -> Graph {
+        let mut graph = Graph::new();
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        for _ in 0..m {
+            graph.add_edge(rng.gen_range(0..n), rng.gen_range(0..n), 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn bfs_matches() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let graph = random_directed(60, 90, &mut rng);
+            let csr = Csr::new(&graph);
+            for start in [0, 17, 59] {
+                let expected: Vec<NodeId> = algo::Bfs::new(&graph, start).collect();
+                assert_eq!(bfs(&csr, start), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn pagerank_matches() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let graphs = vec![
+            random_directed(200, 600, &mut rng),
+            generators::barabasi_albert(200, 3, &mut rng),
+            Graph::new(),
+        ];
+        for graph in graphs {
+            let csr = Csr::new(&graph);
+            let expected = centrality::pagerank(&graph, 0.85, 1e-10, 100).unwrap();
+            assert_eq!(pagerank(&csr, 0.85, 1e-10, 100).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn components_match() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..20 {
+            let graph = random_directed(80, 60, &mut rng);
+            let csr = Csr::new(&graph);
+            assert_eq!(
+                connected_components(&csr),
+                algo::connected_components(&graph)
+            );
+        }
+        let path = generators::path(500);
+        let csr = Csr::new(&path);
+        assert_eq!(connected_components(&csr), vec![0; 500]);
+        assert_eq!(csr.entry_count(), 998);
+    }
+
+    #[test]
+    fn hidden_nodes() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let graph = random_directed(60, 120, &mut rng);
+        let view = graph.filter_nodes(|node| node.id() % 4 != 0);
+        let csr = Csr::new(&view);
+        assert!(!csr.contains_node(0));
+        assert_eq!(
+            connected_components(&csr),
+            algo::connected_components(&view)
+        );
+        let expected = centrality::pagerank(&view, 0.85, 1e-10, 100).unwrap();
+        assert_eq!(pagerank(&csr, 0.85, 1e-10, 100).unwrap(), expected);
+    }
+}
@@ -0,0 +1,617 @@
+//! Planarity testing with the left-right algorithm of de Fraysseix and
+//! Rosenstiehl, following the presentation of Brandes. Edge direction, self
+//! loops and parallel edges do not matter for planarity and are ignored.
+
+use std::collections::HashSet;
+
+use crate::prelude::{IntoNeighbors, NodeCount, NodeId};
+
+/// A combinatorial embedding in the plane: the neighbors of every node, in
+/// clockwise order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embedding {
+    rotations: Vec<Vec<NodeId>>,
+}
+
+impl Embedding {
+    /// The neighbors of `node`, in clockwise order around it
+    pub fn neighbors(&self, node: NodeId) -> &[NodeId] {
+        &self.rotations[node]
+    }
+
+    /// The faces of the embedding, each given by the nodes met while walking
+    /// along its border. Isolated nodes have no face.
+    pub fn faces(&self) -> Vec<Vec<NodeId>> {
+        let mut seen = HashSet::new();
+        let mut faces = vec![];
+        for (node, rotation) in self.rotations.iter().enumerate() {
+            for &next in rotation {
+                if seen.contains(&(node, next)) {
+                    continue;
+                }
+
+                // Leave every node through the edge right before the one we came from
+                let mut face = vec![];
+                let (mut from, mut to) = (node, next);
+                while seen.insert((from, to)) {
+                    face.push(from);
+                    let around = &self.rotations[to];
+                    let back = around.iter().position(|n| *n == from).unwrap();
+                    let turn = around[(back + around.len() - 1) % around.len()];
+                    from = to;
+                    to = turn;
+                }
+                faces.push(face);
+            }
+        }
+
+        faces
+    }
+}
+
+/// Returned for non planar graphs, holding a Kuratowski subgraph: a subdivision
+/// of K₅ or K₃,₃ whose edges are all in the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonPlanar {
+    /// The edges of the subgraph, with the smaller endpoint first
+    pub edges: Vec<(NodeId, NodeId)>,
+}
+
+/// Tests whether `graph` can be drawn in the plane without crossing edges.
+/// Returns an embedding doing so when it can, and a Kuratowski subgraph proving
+/// it cannot otherwise.
+///
+/// The test itself takes linear time. Finding the Kuratowski subgraph repeats
+/// it on shrinking sets of edges, about k log m times for a subgraph of k edges
+/// out of m.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{generators, planarity::is_planar};
+/// let embedding = is_planar(&generators::complete(4)).unwrap();
+/// assert_eq!(embedding.faces().len(), 4);
+///
+/// let error = is_planar(&generators::complete(5)).unwrap_err();
+/// assert_eq!(error.edges.len(), 10);
+/// ```
+pub fn is_planar<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Embedding, NonPlanar> {
+    let n = graph.node_count();
+    let mut edges = vec![];
+    for node in 0..n {
+        for next in graph.neighbors(node).filter(|next| *next != node) {
+            edges.push((node.min(next), node.max(next)));
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+
+    if let Some(rotations) = LeftRight::new(n, &edges).run() {
+        return Ok(Embedding { rotations });
+    }
+
+    // Dropping every edge whose removal keeps the graph non planar leaves an
+    // edge minimal non planar subgraph, which by Kuratowski's theorem is a
+    // subdivision of K₅ or K₃,₃. Edges are dropped in runs, which grow while
+    // they can be dropped and shrink down to a single edge when they cannot.
+    let mut kept = edges;
+    let mut i = 0;
+    let mut run = (kept.len() / 2).max(1);
+    while i < kept.len() {
+        let end = (i + run).min(kept.len());
+        let rest: Vec<(NodeId, NodeId)> = kept[..i].iter().chain(&kept[end..]).copied().collect();
+        if LeftRight::new(n, &rest).run().is_none() {
+            kept = rest;
+            run *= 2;
+        } else if end - i > 1 {
+            run = (end - i) / 2;
+        } else {
+            i += 1;
+        }
+    }
+
+    Err(NonPlanar { edges: kept })
+}
+
+/// The edges, as indices of the oriented edges, in the two halves of a conflict pair
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Interval {
+    low: Option<usize>,
+    high: Option<usize>,
+}
+
+impl Interval {
+    fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ConflictPair {
+    left: Interval,
+    right: Interval,
+}
+
+impl ConflictPair {
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+}
+
+/// The state of the left-right test. Every edge gets a direction during the
+/// first depth first search and is identified by its index in `edges` from then on.
+///
+/// The three searches keep their own stacks, along with the position reached in
+/// the edge list of every node, so deep graphs cannot overflow the call stack.
+struct LeftRight {
+    adjacency: Vec<Vec<(NodeId, usize)>>,
+    edges: Vec<(NodeId, NodeId)>,
+    oriented: Vec<bool>,
+    outgoing: Vec<Vec<usize>>,
+    roots: Vec<NodeId>,
+    height: Vec<Option<usize>>,
+    parent_edge: Vec<Option<usize>>,
+    lowpt: Vec<usize>,
+    lowpt2: Vec<usize>,
+    nesting_depth: Vec<isize>,
+    refs: Vec<Option<usize>>,
+    side: Vec<isize>,
+    stack: Vec<ConflictPair>,
+    stack_bottom: Vec<usize>,
+    lowpt_edge: Vec<Option<usize>>,
+    left_ref: Vec<NodeId>,
+    right_ref: Vec<NodeId>,
+}
+
+impl LeftRight {
+    fn new(n: usize, edges: &[(NodeId, NodeId)]) -> Self {
+        let m = edges.len();
+        let mut adjacency = vec![vec![]; n];
+        for (id, (a, b)) in edges.iter().enumerate() {
+            adjacency[*a].push((*b, id));
+            adjacency[*b].push((*a, id));
+        }
+
+        Self {
+            adjacency,
+            edges: edges.to_vec(),
+            oriented: vec![false; m],
+            outgoing: vec![vec![]; n],
+            roots: vec![],
+            height: vec![None; n],
+            parent_edge: vec![None; n],
+            lowpt: vec![0; m],
+            lowpt2: vec![0; m],
+            nesting_depth: vec![0; m],
+            refs: vec![None; m],
+            side: vec![1; m],
+            stack: vec![],
+            stack_bottom: vec![0; m],
+            lowpt_edge: vec![None; m],
+            left_ref: vec![0; n],
+            right_ref: vec![0; n],
+        }
+    }
+
+    /// Returns the clockwise rotation of every node if the graph is planar
+    fn run(mut self) -> Option<Vec<Vec<NodeId>>> {
+        let n = self.adjacency.len();
+        if n > 2 && self.edges.len() > 3 * n - 6 {
+            return None;
+        }
+
+        let mut cursors = vec![0; n];
+        for node in 0..n {
+            if self.height[node].is_none() {
+                self.height[node] = Some(0);
+                self.roots.push(node);
+                self.orient(node, &mut cursors);
+            }
+        }
+
+        self.sort_outgoing();
+        cursors.fill(0);
+        for root in self.roots.clone() {
+            if !self.test(root, &mut cursors) {
+                return None;
+            }
+        }
+
+        for e in 0..self.edges.len() {
+            self.nesting_depth[e] *= self.sign(e);
+        }
+        self.sort_outgoing();
+        let mut rotations: Vec<Vec<NodeId>> = self
+            .outgoing
+            .iter()
+            .map(|out| out.iter().map(|e| self.edges[*e].1).collect())
+            .collect();
+        cursors.fill(0);
+        for root in self.roots.clone() {
+            self.embed(root, &mut rotations, &mut cursors);
+        }
+
+        Some(rotations)
+    }
+
+    fn sort_outgoing(&mut self) {
+        let depth = &self.nesting_depth;
+        for out in &mut self.outgoing {
+            out.sort_by_key(|e| depth[*e]);
+        }
+    }
+
+    /// First pass: orients the edges along a depth first search and computes
+    /// their lowpoints and nesting depths. A node is resumed from the stack
+    /// once the search below its current tree edge is done.
+    fn orient(&mut self, root: NodeId, cursors: &mut [usize]) {
+        let mut stack = vec![(root, false)];
+        'nodes: while let Some((v, mut resumed)) = stack.pop() {
+            let parent = self.parent_edge[v];
+            let height = self.height[v].unwrap();
+            while cursors[v] < self.adjacency[v].len() {
+                let (w, e) = self.adjacency[v][cursors[v]];
+                if !resumed {
+                    if self.oriented[e] {
+                        cursors[v] += 1;
+                        continue;
+                    }
+                    self.oriented[e] = true;
+                    self.edges[e] = (v, w);
+                    self.outgoing[v].push(e);
+                    self.lowpt[e] = height;
+                    self.lowpt2[e] = height;
+
+                    match self.height[w] {
+                        None => {
+                            self.parent_edge[w] = Some(e);
+                            self.height[w] = Some(height + 1);
+                            stack.push((v, true));
+                            stack.push((w, false));
+                            continue 'nodes;
+                        }
+                        Some(h) => self.lowpt[e] = h,
+                    }
+                }
+                resumed = false;
+
+                // Chordal edges nest outside of the others with the same lowpoint
+                self.nesting_depth[e] = 2 * self.lowpt[e] as isize;
+                if self.lowpt2[e] < height {
+                    self.nesting_depth[e] += 1;
+                }
+
+                if let Some(p) = parent {
+                    if self.lowpt[e] < self.lowpt[p] {
+                        self.lowpt2[p] = self.lowpt[p].min(self.lowpt2[e]);
+                        self.lowpt[p] = self.lowpt[e];
+                    } else if self.lowpt[e] > self.lowpt[p] {
+                        self.lowpt2[p] = self.lowpt2[p].min(self.lowpt[e]);
+                    } else {
+                        self.lowpt2[p] = self.lowpt2[p].min(self.lowpt2[e]);
+                    }
+                }
+                cursors[v] += 1;
+            }
+        }
+    }
+
+    /// Second pass: checks that the return edges can be split between the two
+    /// sides of the tree without conflicts
+    fn test(&mut self, root: NodeId, cursors: &mut [usize]) -> bool {
+        let mut stack = vec![(root, false)];
+        'nodes: while let Some((v, mut resumed)) = stack.pop() {
+            let parent = self.parent_edge[v];
+            let height = self.height[v].unwrap();
+            while cursors[v] < self.outgoing[v].len() {
+                let i = cursors[v];
+                let e = self.outgoing[v][i];
+                if !resumed {
+                    let w = self.edges[e].1;
+                    self.stack_bottom[e] = self.stack.len();
+                    if self.parent_edge[w] == Some(e) {
+                        stack.push((v, true));
+                        stack.push((w, false));
+                        continue 'nodes;
+                    }
+                    self.lowpt_edge[e] = Some(e);
+                    self.stack.push(ConflictPair {
+                        left: Interval::default(),
+                        right: Interval {
+                            low: Some(e),
+                            high: Some(e),
+                        },
+                    });
+                }
+                resumed = false;
+
+                if self.lowpt[e] < height {
+                    let p = parent.unwrap();
+                    if i == 0 {
+                        self.lowpt_edge[p] = self.lowpt_edge[e];
+                    } else if !self.add_constraints(e, p) {
+                        return false;
+                    }
+                }
+                cursors[v] += 1;
+            }
+
+            if let Some(p) = parent {
+                self.remove_back_edges(p);
+            }
+        }
+
+        true
+    }
+
+    fn conflicting(&self, interval: &Interval, e: usize) -> bool {
+        interval.high.is_some_and(|h| self.lowpt[h] > self.lowpt[e])
+    }
+
+    fn lowest(&self, pair: &ConflictPair) -> usize {
+        let low = |interval: &Interval| interval.low.map(|l| self.lowpt[l]);
+        match (low(&pair.left), low(&pair.right)) {
+            (Some(l), Some(r)) => l.min(r),
+            (l, r) => l.or(r).unwrap(),
+        }
+    }
+
+    fn add_constraints(&mut self, e: usize, parent: usize) -> bool {
+        let mut merged = ConflictPair::default();
+
+        // The return edges of `e` all go to the right of `merged`
+        loop {
+            let mut pair = self.stack.pop().unwrap();
+            if !pair.left.is_empty() {
+                pair.swap();
+            }
+            if !pair.left.is_empty() {
+                return false;
+            }
+
+            let low = pair.right.low.unwrap();
+            if self.lowpt[low] > self.lowpt[parent] {
+                if merged.right.is_empty() {
+                    merged.right = pair.right;
+                } else if let Some(l) = merged.right.low {
+                    self.refs[l] = pair.right.high;
+                }
+                merged.right.low = pair.right.low;
+            } else {
+                self.refs[low] = self.lowpt_edge[parent];
+            }
+
+            if self.stack.len() == self.stack_bottom[e] {
+                break;
+            }
+        }
+
+        // The return edges of the earlier siblings that conflict with `e` go left
+        while let Some(top) = self.stack.last() {
+            if !self.conflicting(&top.left, e) && !self.conflicting(&top.right, e) {
+                break;
+            }
+            let mut pair = self.stack.pop().unwrap();
+            if self.conflicting(&pair.right, e) {
+                pair.swap();
+            }
+            if self.conflicting(&pair.right, e) {
+                return false;
+            }
+
+            if let Some(l) = merged.right.low {
+                self.refs[l] = pair.right.high;
+            }
+            if pair.right.low.is_some() {
+                merged.right.low = pair.right.low;
+            }
+            if merged.left.is_empty() {
+                merged.left = pair.left;
+            } else if let Some(l) = merged.left.low {
+                self.refs[l] = pair.left.high;
+            }
+            merged.left.low = pair.left.low;
+        }
+
+        if !merged.left.is_empty() || !merged.right.is_empty() {
+            self.stack.push(merged);
+        }
+
+        true
+    }
+
+    fn remove_back_edges(&mut self, e: usize) {
+        let u = self.edges[e].0;
+        let height = self.height[u].unwrap();
+
+        // Pairs whose lowest return edge ends at `u` are done with
+        while self
+            .stack
+            .last()
+            .is_some_and(|top| self.lowest(top) == height)
+        {
+            let pair = self.stack.pop().unwrap();
+            if let Some(l) = pair.left.low {
+                self.side[l] = -1;
+            }
+        }
+
+        if let Some(mut pair) = self.stack.pop() {
+            // Trims the return edges ending at `u` from the top of both intervals
+            while let Some(h) = pair.left.high.filter(|h| self.edges[*h].1 == u) {
+                pair.left.high = self.refs[h];
+            }
+            if let (None, Some(l)) = (pair.left.high, pair.left.low) {
+                self.refs[l] = pair.right.low;
+                self.side[l] = -1;
+                pair.left.low = None;
+            }
+
+            while let Some(h) = pair.right.high.filter(|h| self.edges[*h].1 == u) {
+                pair.right.high = self.refs[h];
+            }
+            if let (None, Some(l)) = (pair.right.high, pair.right.low) {
+                self.refs[l] = pair.left.low;
+                self.side[l] = -1;
+                pair.right.low = None;
+            }
+            self.stack.push(pair);
+        }
+
+        // `e` goes on the side of its highest return edge
+        if self.lowpt[e] < height {
+            let top = self.stack.last().unwrap();
+            let (left, right) = (top.left.high, top.right.high);
+            self.refs[e] = match (left, right) {
+                (Some(l), Some(r)) if self.lowpt[l] > self.lowpt[r] => left,
+                (Some(_), None) => left,
+                _ => right,
+            };
+        }
+    }
+
+    /// The final side of `e`, relative to its parent edge. Follows the chain of
+    /// references from `e`, then fixes the sides from its far end back.
+    fn sign(&mut self, e: usize) -> isize {
+        let mut chain = vec![];
+        let mut current = e;
+        while let Some(r) = self.refs[current].take() {
+            chain.push(current);
+            current = r;
+        }
+
+        let mut side = self.side[current];
+        while let Some(f) = chain.pop() {
+            self.side[f] *= side;
+            side = self.side[f];
+        }
+        side
+    }
+
+    /// Last pass: inserts the back edges in the rotations of the nodes they return to
+    fn embed(&mut self, root: NodeId, rotations: &mut [Vec<NodeId>], cursors: &mut [usize]) {
+        let mut stack = vec![root];
+        while let Some(&v) = stack.last() {
+            let e = match self.outgoing[v].get(cursors[v]) {
+                Some(e) => *e,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            cursors[v] += 1;
+
+            let w = self.edges[e].1;
+            if self.parent_edge[w] == Some(e) {
+                rotations[w].insert(0, v);
+                self.left_ref[v] = w;
+                self.right_ref[v] = w;
+                stack.push(w);
+            } else if self.side[e] == 1 {
+                let position = rotations[w].iter().position(|n| *n == self.right_ref[w]);
+                rotations[w].insert(position.unwrap() + 1, v);
+            } else {
+                let position = rotations[w].iter().position(|n| *n == self.left_ref[w]);
+                rotations[w].insert(position.unwrap(), v);
+                self.left_ref[w] = v;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+
+    /// Checks Euler's formula, which only holds for genuine planar embeddings
+    fn assert_embedding<G: IntoNeighbors + NodeCount>(graph: &G) {
+        let embedding = is_planar(graph).expect("The graph is planar");
+        let edges: usize = (0..graph.node_count())
+            .map(|node| embedding.neighbors(node).len())
+            .sum::<usize>()
+            / 2;
+        let faces = embedding.faces().len();
+        assert_eq!(graph.node_count() + faces, edges + 2);
+    }
+
+    fn complete_bipartite(a: usize, b: usize) -> Graph {
+        let mut graph = Graph::new_undirected();
+        for _ in 0..a + b {
+            graph.add_node(());
+        }
+        for x in 0..a {
+            for y in a..a + b {
+                graph.add_edge(x, y, 1);
+            }
+        }
+
+        graph
+    }
+
+    #[test]
+    fn planar_graphs() {
+        assert_embedding(&generators::complete(4));
+        assert_embedding(&generators::grid(4, 5));
+        assert_embedding(&generators::wheel(8));
+        assert_embedding(&generators::hypercube(3));
+        assert_embedding(&complete_bipartite(2, 5));
+        assert_embedding(&generators::path(3));
+    }
+
+    #[test]
+    fn deep_graphs() {
+        assert_embedding(&generators::path(100_000));
+
+        // A caterpillar: a long spine with a leg on every node
+        let mut caterpillar = generators::path(60_000);
+        for spine in 0..60_000 {
+            let leg = caterpillar.add_node(());
+            caterpillar.add_edge(spine, leg, 1);
+        }
+        assert_embedding(&caterpillar);
+
+        let mut cycle = generators::cycle(100_000);
+        cycle.add_edge(0, 50_000, 1);
+        assert_embedding(&cycle);
+    }
+
+    #[test]
+    fn kuratowski_subgraphs() {
+        let error = is_planar(&complete_bipartite(3, 3)).unwrap_err();
+        assert_eq!(error.edges.len(), 9);
+
+        // The Petersen graph contains a subdivision of K₃,₃ on 9 of its nodes
+        let error = is_planar(&generators::petersen()).unwrap_err();
+        let mut subgraph = Graph::new_undirected();
+        for _ in 0..10 {
+            subgraph.add_node(());
+        }
+        for (a, b) in &error.edges {
+            subgraph.add_edge(*a, *b, 1);
+        }
+        assert!(is_planar(&subgraph).is_err());
+        let degrees: Vec<usize> = (0..10).map(|n| subgraph.neighbors(n).count()).collect();
+        assert_eq!(degrees.iter().filter(|d| **d == 3).count(), 6);
+        assert!(degrees.iter().all(|d| *d == 0 || *d == 2 || *d == 3));
+    }
+
+    #[test]
+    fn directions_and_duplicates_are_ignored() {
+        let mut graph = Graph::new();
+        for _ in 0..5 {
+            graph.add_node(());
+        }
+        for a in 0..5 {
+            for b in 0..5 {
+                graph.add_edge(a, b, 1);
+            }
+        }
+        assert_eq!(is_planar(&graph).unwrap_err().edges.len(), 10);
+
+        let mut k5 = generators::complete(5);
+        k5.add_edge(0, 1, 1);
+        assert_embedding(
+            &k5.filter_edges(|e| e.source.min(e.target) != 0 || e.source.max(e.target) != 1),
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Reachability on directed acyclic graphs: transitive closure and reduction,
+//! and a precomputed index answering reachability queries in constant time.
+
+use super::Graph;
+use crate::{
+    algo::{toposort, Cycle},
+    prelude::{IntoNeighbors, NodeCount, NodeId},
+};
+
+/// Answers "can `a` reach `b`?" in O(1), by storing the set of descendants of
+/// every node as a bitset. Takes n² bits of memory.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{Graph, reachability::ReachabilityIndex};
+/// let mut graph = Graph::new();
+/// for _ in 0..3 {
+///     graph.add_node(());
+/// }
+/// graph.add_edge(0, 1, 1);
+/// graph.add_edge(1, 2, 1);
+///
+/// let index = ReachabilityIndex::new(&graph).unwrap();
+/// assert!(index.reaches(0, 2));
+/// assert!(!index.reaches(2, 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReachabilityIndex {
+    nodes: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl ReachabilityIndex {
+    /// Builds the index of a directed acyclic graph. Fails if the graph has a cycle.
+    pub fn new<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Self, Cycle> {
+        let n = graph.node_count();
+        let order = toposort(graph)?;
+        let words = n.div_ceil(64);
+        let mut index = ReachabilityIndex {
+            nodes: n,
+            words,
+            bits: vec![0; n * words],
+        };
+
+        // Children come later in the order, so their sets are complete when merged
+        for &node in order.iter().rev() {
+            index.set(node, node);
+            for next in graph.neighbors(node) {
+                for word in 0..words {
+                    index.bits[node * words + word] |= index.bits[next * words + word];
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    fn set(&mut self, from: NodeId, to: NodeId) {
+        self.bits[from * self.words + to / 64] |= 1 << (to % 64);
+    }
+
+    /// Returns `true` if there is a path from `a` to `b`. Every node reaches itself.
+    pub fn reaches(&self, a: NodeId, b: NodeId) -> bool {
+        self.bits[a * self.words + b / 64] & (1 << (b % 64)) != 0
+    }
+
+    /// Every node reachable from `node`, itself included, in id order
+    pub fn descendants(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes).filter(move |b| self.reaches(node, *b))
+    }
+}
+
+/// Builds a graph with the same nodes as `graph` and an edge from each node to
+/// every other node it can reach. Fails if the graph has a cycle.
+pub fn transitive_closure<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Graph, Cycle> {
+    let index = ReachabilityIndex::new(graph)?;
+    let mut closure = Graph::new();
+    for _ in 0..graph.node_count() {
+        closure.add_node(());
+    }
+    for node in 0..graph.node_count() {
+        for target in index.descendants(node).filter(|t| *t != node) {
+            closure.add_edge(node, target, 1);
+        }
+    }
+
+    Ok(closure)
+}
+
+/// Builds the smallest graph with the same nodes and the same reachability as
+/// `graph`, by dropping every edge that a longer path already implies. Parallel
+/// edges are merged. Fails if the graph has a cycle.
+pub fn transitive_reduction<G: IntoNeighbors + NodeCount>(graph: &G) -> Result<Graph, Cycle> {
+    let index = ReachabilityIndex::new(graph)?;
+    let mut reduction = Graph::new();
+    for _ in 0..graph.node_count() {
+        reduction.add_node(());
+    }
+    for node in 0..graph.node_count() {
+        let mut children: Vec<NodeId> = graph.neighbors(node).collect();
+        children.sort_unstable();
+        children.dedup();
+        for &child in &children {
+            let implied = children
+                .iter()
+                .any(|other| *other != child && index.reaches(*other, child));
+            if !implied {
+                reduction.add_edge(node, child, 1);
+            }
+        }
+    }
+
+    Ok(reduction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(graph: &Graph) -> Vec<(NodeId, NodeId)> {
+        graph.edges().map(|e| (e.from(), e.to())).collect()
+    }
+
+    fn make_test_graph() -> Graph {
+        let mut graph = Graph::new();
+        for _ in 0..5 {
+            graph.add_node(());
+        }
+        for (a, b) in [(0, 1), (1, 2), (0, 2), (2, 3), (0, 3), (4, 3)].iter() {
+            graph.add_edge(*a, *b, 1);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn closure() {
+        let closure = transitive_closure(&make_test_graph()).unwrap();
+        assert_eq!(
+            edges(&closure),
+            vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (4, 3)]
+        );
+    }
+
+    #[test]
+    fn reduction() {
+        let reduction = transitive_reduction(&make_test_graph()).unwrap();
+        assert_eq!(edges(&reduction), vec![(0, 1), (1, 2), (2, 3), (4, 3)]);
+    }
+
+    #[test]
+    fn index_spans_several_words() {
+        let mut graph = Graph::new();
+        for _ in 0..150 {
+            graph.add_node(());
+        }
+        for node in 1..150 {
+            graph.add_edge(node - 1, node, 1);
+        }
+
+        let index = ReachabilityIndex::new(&graph).unwrap();
+        assert!(index.reaches(3, 140));
+        assert!(!index.reaches(140, 3));
+        assert_eq!(index.descendants(100).count(), 50);
+
+        graph.add_edge(149, 0, 1);
+        assert!(ReachabilityIndex::new(&graph).is_err());
+        assert!(transitive_reduction(&graph).is_err());
+    }
+}
@@ -0,0 +1,527 @@
+//! Traveling salesman tours. A tour visits every node exactly once and returns
+//! to its first node. It is given as the list of nodes in visiting order, along
+//! with its total cost. Edge weights are used as costs. Between two nodes, only
+//! the lightest edge counts.
+//!
+//! The solvers that build a tour return `None` when the graph has no tour they
+//! can find. This happens when edges are missing, so complete graphs always get one.
+
+use crate::{
+    algo::TooManyNodes,
+    prelude::{IntoEdges, NodeCount, NodeId},
+};
+
+/// The largest graph accepted by `held_karp`
+pub const EXACT_LIMIT: usize = 18;
+
+/// The lightest edge between every ordered pair of nodes
+struct Distances {
+    n: usize,
+    weights: Vec<Option<usize>>,
+}
+
+impl Distances {
+    fn new<G: IntoEdges + NodeCount>(graph: &G) -> Self {
+        let n = graph.node_count();
+        let mut weights = vec![None; n * n];
+        for node in 0..n {
+            for edge in graph.edges(node).filter(|e| e.target != node) {
+                let weight = &mut weights[node * n + edge.target];
+                if weight.is_none_or(|w| edge.weight < w) {
+                    *weight = Some(edge.weight);
+                }
+            }
+        }
+
+        Self { n, weights }
+    }
+
+    fn get(&self, from: NodeId, to: NodeId) -> Option<usize> {
+        self.weights[from * self.n + to]
+    }
+
+    /// The cost of a path through `nodes`, in order
+    fn path(&self, nodes: &[NodeId]) -> Option<usize> {
+        nodes.windows(2).map(|w| self.get(w[0], w[1])).sum()
+    }
+
+    fn tour(&self, tour: &[NodeId]) -> Option<usize> {
+        match (tour.first(), tour.last()) {
+            (Some(first), Some(last)) if tour.len() > 1 => {
+                Some(self.path(tour)? + self.get(*last, *first)?)
+            }
+            _ => Some(0),
+        }
+    }
+}
+
+/// Builds a tour from `start` by always moving to the closest unvisited node
+///
+/// Panics if `start` does not exist.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{generators, tsp::nearest_neighbor};
+/// let graph = generators::complete(4);
+/// assert_eq!(nearest_neighbor(&graph, 2), Some((4, vec![2, 0, 1, 3])));
+/// ```
+pub fn nearest_neighbor<G: IntoEdges + NodeCount>(
+    graph: &G,
+    start: NodeId,
+) -> Option<(usize, Vec<NodeId>)> {
+    let distances = Distances::new(graph);
+    assert!(start < distances.n, "The start must be a node of the graph");
+    let mut visited = vec![false; distances.n];
+    let mut tour = vec![start];
+    visited[start] = true;
+    for _ in 1..distances.n {
+        let last = *tour.last().unwrap();
+        let next = (0..distances.n)
+            .filter(|node| !visited[*node])
+            .filter_map(|node| Some((distances.get(last, node)?, node)))
+            .min()?
+            .1;
+        visited[next] = true;
+        tour.push(next);
+    }
+
+    Some((distances.tour(&tour)?, tour))
+}
+
+/// Christofides' approximation: joins a minimum spanning tree with a minimum
+/// weight matching of its odd degree nodes, then shortcuts an Euler circuit of
+/// the result. On complete graphs whose weights obey the triangle inequality, the
+/// tour costs at most 3/2 of the optimal one. The tour starts at node 0.
+///
+/// The matching is exact for up to `EXACT_LIMIT` odd degree nodes and greedy
+/// beyond that, in which case the 3/2 bound is no longer guaranteed.
+///
+/// Panics if the graph is directed.
+pub fn christofides<G: IntoEdges + NodeCount>(graph: &G) -> Option<(usize, Vec<NodeId>)> {
+    assert!(
+        !graph.is_directed(),
+        "Christofides needs an undirected graph"
+    );
+    let distances = Distances::new(graph);
+    let n = distances.n;
+    if n < 2 {
+        return Some((0, (0..n).collect()));
+    }
+
+    // Prim's algorithm on the distance matrix
+    let mut in_tree = vec![false; n];
+    let mut best: Vec<Option<(usize, NodeId)>> = vec![None; n];
+    let mut multigraph = vec![];
+    best[0] = Some((0, 0));
+    for _ in 0..n {
+        let node = (0..n)
+            .filter(|node| !in_tree[*node])
+            .filter_map(|node| Some((best[node]?, node)))
+            .min()?
+            .1;
+        in_tree[node] = true;
+        if node != 0 {
+            multigraph.push((best[node].unwrap().1, node));
+        }
+        for next in (0..n).filter(|next| !in_tree[*next]) {
+            if let Some(w) = distances.get(node, next) {
+                if best[next].is_none_or(|(b, _)| w < b) {
+                    best[next] = Some((w, node));
+                }
+            }
+        }
+    }
+
+    let mut degrees = vec![0; n];
+    for (a, b) in &multigraph {
+        degrees[*a] += 1;
+        degrees[*b] += 1;
+    }
+    let odd: Vec<NodeId> = (0..n).filter(|node| degrees[*node] % 2 == 1).collect();
+    multigraph.extend(min_matching(&distances, &odd)?);
+
+    // Hierholzer's algorithm, then every node is kept at its first visit
+    let mut incident = vec![vec![]; n];
+    for (i, (a, b)) in multigraph.iter().enumerate() {
+        incident[*a].push(i);
+        incident[*b].push(i);
+    }
+    let mut used = vec![false; multigraph.len()];
+    let mut stack = vec![0];
+    let mut visited = vec![false; n];
+    let mut tour = vec![];
+    while let Some(&node) = stack.last() {
+        match incident[node].iter().copied().find(|e| !used[*e]) {
+            Some(e) => {
+                used[e] = true;
+                let (a, b) = multigraph[e];
+                stack.push(if a == node { b } else { a });
+            }
+            None => {
+                stack.pop();
+                if !visited[node] {
+                    visited[node] = true;
+                    tour.push(node);
+                }
+            }
+        }
+    }
+    tour.reverse();
+    let first = tour.iter().position(|node| *node == 0).unwrap();
+    tour.rotate_left(first);
+
+    Some((distances.tour(&tour)?, tour))
+}
+
+/// Pairs up `nodes` with the smallest total weight
+fn min_matching(distances: &Distances, nodes: &[NodeId]) -> Option<Vec<(NodeId, NodeId)>> {
+    let m = nodes.len();
+    if m > EXACT_LIMIT {
+        let mut pairs: Vec<(usize, NodeId, NodeId)> = nodes
+            .iter()
+            .flat_map(|a| nodes.iter().map(move |b| (*a, *b)))
+            .filter(|(a, b)| a < b)
+            .filter_map(|(a, b)| Some((distances.get(a, b)?, a, b)))
+            .collect();
+        pairs.sort_unstable();
+        let mut matched = vec![false; distances.n];
+        let mut matching = vec![];
+        for (_, a, b) in pairs {
+            if !matched[a] && !matched[b] {
+                matched[a] = true;
+                matched[b] = true;
+                matching.push((a, b));
+            }
+        }
+        return if matching.len() * 2 == m {
+            Some(matching)
+        } else {
+            None
+        };
+    }
+
+    // costs[mask] is the cheapest matching of the nodes in `mask`, found by
+    // pairing its lowest node with each of the others
+    let full = (1usize << m) - 1;
+    let mut costs: Vec<Option<(usize, usize)>> = vec![None; full + 1];
+    costs[0] = Some((0, 0));
+    for mask in 1..=full {
+        if mask.count_ones() % 2 == 1 {
+            continue;
+        }
+        let i = mask.trailing_zeros() as usize;
+        for j in (i + 1..m).filter(|j| mask & 1 << j != 0) {
+            let rest = mask & !(1 << i) & !(1 << j);
+            let cost = match (costs[rest], distances.get(nodes[i], nodes[j])) {
+                (Some((c, _)), Some(w)) => c + w,
+                _ => continue,
+            };
+            if costs[mask].is_none_or(|(c, _)| cost < c) {
+                costs[mask] = Some((cost, j));
+            }
+        }
+    }
+
+    let mut matching = vec![];
+    let mut mask = full;
+    while mask != 0 {
+        let i = mask.trailing_zeros() as usize;
+        let j = costs[mask]?.1;
+        matching.push((nodes[i], nodes[j]));
+        mask &= !(1 << i) & !(1 << j);
+    }
+
+    Some(matching)
+}
+
+/// Improves `tour` by reversing sections of it for as long as that makes it
+/// cheaper, until no reversal helps
+///
+/// Panics if `tour` uses a missing edge.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::{generators, tsp::two_opt};
+/// // The tour 0 2 1 3 of a square crosses itself
+/// let mut graph = generators::cycle(4);
+/// graph.add_edge(0, 2, 5);
+/// graph.add_edge(1, 3, 5);
+/// assert_eq!(two_opt(&graph, &[0, 2, 1, 3]), (4, vec![0, 1, 2, 3]));
+/// ```
+pub fn two_opt<G: IntoEdges + NodeCount>(graph: &G, tour: &[NodeId]) -> (usize, Vec<NodeId>) {
+    let distances = Distances::new(graph);
+    let mut tour = tour.to_vec();
+    let mut cost = distances.tour(&tour).expect("The tour uses a missing edge");
+    let n = tour.len();
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..n {
+            // The cost of `tour[i..=j]` walked forwards and backwards, which
+            // differ on directed graphs
+            let (mut forward, mut backward) = (Some(0), Some(0));
+            for j in i + 1..n {
+                forward = Some(forward.unwrap() + distances.get(tour[j - 1], tour[j]).unwrap());
+                backward = backward.and_then(|b| Some(b + distances.get(tour[j], tour[j - 1])?));
+                let (before, after) = (tour[i - 1], tour[(j + 1) % n]);
+                let removed = distances.get(before, tour[i]).unwrap()
+                    + forward.unwrap()
+                    + distances.get(tour[j], after).unwrap();
+                let added = backward.and_then(|b| {
+                    Some(distances.get(before, tour[j])? + b + distances.get(tour[i], after)?)
+                });
+                if let Some(added) = added.filter(|a| *a < removed) {
+                    tour[i..=j].reverse();
+                    cost = cost - removed + added;
+                    std::mem::swap(&mut forward, &mut backward);
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    (cost, tour)
+}
+
+/// Improves `tour` by moving runs of one to three consecutive nodes elsewhere
+/// in it for as long as that makes it cheaper, until no move helps
+///
+/// Panics if `tour` uses a missing edge.
+pub fn or_opt<G: IntoEdges + NodeCount>(graph: &G, tour: &[NodeId]) -> (usize, Vec<NodeId>) {
+    let distances = Distances::new(graph);
+    let mut tour = tour.to_vec();
+    let mut cost = distances.tour(&tour).expect("The tour uses a missing edge");
+    let n = tour.len();
+    let weight = |a, b| distances.get(a, b);
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for length in 1..=3.min(n.saturating_sub(2)) {
+            for start in 1..=n - length {
+                // The tour without the run, indexed as if the run was removed
+                let rest = |k: usize| tour[if k < start { k } else { k + length }];
+                let (first, last) = (tour[start], tour[start + length - 1]);
+                let (before, after) = (tour[start - 1], tour[(start + length) % n]);
+                let lifted = weight(before, first).unwrap() + weight(last, after).unwrap();
+                let closed = weight(before, after);
+
+                let remaining = n - length;
+                let best = (1..=remaining)
+                    .filter(|p| *p != start)
+                    .find_map(|position| {
+                        let (x, y) = (rest(position - 1), rest(position % remaining));
+                        let removed = lifted + weight(x, y).unwrap();
+                        let added = closed? + weight(x, first)? + weight(last, y)?;
+                        Some((position, removed, added)).filter(|_| added < removed)
+                    });
+                if let Some((position, removed, added)) = best {
+                    let run: Vec<NodeId> = tour.drain(start..start + length).collect();
+                    tour.splice(position..position, run);
+                    cost = cost - removed + added;
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    (cost, tour)
+}
+
+/// Finds an optimal tour by dynamic programming over the subsets of nodes, in
+/// O(2ⁿ n²) time. The tour starts at node 0. Returns `Ok(None)` if the graph has
+/// no tour at all.
+pub fn held_karp<G: IntoEdges + NodeCount>(
+    graph: &G,
+) -> Result<Option<(usize, Vec<NodeId>)>, TooManyNodes> {
+    let distances = Distances::new(graph);
+    let n = distances.n;
+    if n > EXACT_LIMIT {
+        return Err(TooManyNodes {
+            nodes: n,
+            limit: EXACT_LIMIT,
+        });
+    }
+    if n < 2 {
+        return Ok(Some((0, (0..n).collect())));
+    }
+
+    // costs[mask * m + last] is the cheapest path from node 0 through the nodes
+    // of `mask` (shifted by one, as node 0 is left out) ending at `last + 1`
+    let m = n - 1;
+    let mut costs: Vec<Option<(usize, usize)>> = vec![None; (1 << m) * m];
+    for last in 0..m {
+        if let Some(w) = distances.get(0, last + 1) {
+            costs[(1 << last) * m + last] = Some((w, usize::MAX));
+        }
+    }
+    for mask in 1usize..1 << m {
+        for last in (0..m).filter(|last| mask & 1 << last != 0) {
+            let (cost, _) = match costs[mask * m + last] {
+                Some(entry) => entry,
+                None => continue,
+            };
+            for next in (0..m).filter(|next| mask & 1 << next == 0) {
+                if let Some(w) = distances.get(last + 1, next + 1) {
+                    let entry = &mut costs[(mask | 1 << next) * m + next];
+                    if entry.is_none_or(|(c, _)| cost + w < c) {
+                        *entry = Some((cost + w, last));
+                    }
+                }
+            }
+        }
+    }
+
+    let full = (1 << m) - 1;
+    let end = (0..m)
+        .filter_map(|last| {
+            let (cost, _) = costs[full * m + last]?;
+            Some((cost + distances.get(last + 1, 0)?, last))
+        })
+        .min();
+    let (cost, mut last) = match end {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+
+    let mut tour = vec![];
+    let mut mask = full;
+    while last != usize::MAX {
+        tour.push(last + 1);
+        let previous = costs[mask * m + last].unwrap().1;
+        mask &= !(1 << last);
+        last = previous;
+    }
+    tour.push(0);
+    tour.reverse();
+
+    Ok(Some((cost, tour)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Graph};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Ten cities on a line, at the positions below, with the distance between
+    /// each pair as weight. The best tour costs twice the span.
+    fn make_test_graph() -> Graph {
+        let positions = [0, 7, 3, 12, 5, 9, 1, 15, 4, 10];
+        let mut graph = Graph::new_undirected();
+        for _ in 0..positions.len() {
+            graph.add_node(());
+        }
+        for a in 0..positions.len() {
+            for b in a + 1..positions.len() {
+                let distance = (positions[a] as isize - positions[b] as isize).unsigned_abs();
+                graph.add_edge(a, b, distance);
+            }
+        }
+
+        graph
+    }
+
+    fn is_tour(tour: &[NodeId], n: usize) -> bool {
+        let mut sorted = tour.to_vec();
+        sorted.sort_unstable();
+        sorted == (0..n).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn exact_and_approximate_tours() {
+        let graph = make_test_graph();
+        let (optimal, tour) = held_karp(&graph).unwrap().unwrap();
+        assert_eq!(optimal, 30);
+        assert!(is_tour(&tour, 10));
+
+        let (cost, tour) = christofides(&graph).unwrap();
+        assert!(is_tour(&tour, 10) && cost * 2 <= optimal * 3);
+
+        let (cost, tour) = nearest_neighbor(&graph, 0).unwrap();
+        assert!(is_tour(&tour, 10) && cost >= optimal);
+    }
+
+    #[test]
+    fn local_search_reaches_the_optimum() {
+        let graph = make_test_graph();
+        let tour: Vec<NodeId> = (0..10).collect();
+        let (cost, improved) = two_opt(&graph, &tour);
+        assert!(is_tour(&improved, 10));
+        assert_eq!(cost, 30);
+
+        let (cost, improved) = or_opt(&graph, &tour);
+        assert!(is_tour(&improved, 10));
+        assert!(cost < Distances::new(&graph).tour(&tour).unwrap());
+    }
+
+    #[test]
+    fn local_search_costs_stay_exact() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for directed in [true, false] {
+            let mut graph = if directed {
+                Graph::new()
+            } else {
+                Graph::new_undirected()
+            };
+            for _ in 0..25 {
+                graph.add_node(());
+            }
+            for a in 0..25 {
+                for b in 0..25 {
+                    if a < b || directed && a != b {
+                        graph.add_edge(a, b, rng.gen_range(1..100));
+                    }
+                }
+            }
+
+            let distances = Distances::new(&graph);
+            let tour: Vec<NodeId> = (0..25).collect();
+            for (cost, improved) in [two_opt(&graph, &tour), or_opt(&graph, &tour)] {
+                assert!(is_tour(&improved, 25));
+                assert_eq!(distances.tour(&improved), Some(cost));
+                assert!(cost < distances.tour(&tour).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn missing_edges() {
+        let star = generators::star(4);
+        assert_eq!(nearest_neighbor(&star, 0), None);
+        assert_eq!(christofides(&star), None);
+        assert_eq!(held_karp(&star), Ok(None));
+
+        let (cost, tour) = held_karp(&generators::cycle(6)).unwrap().unwrap();
+        assert!(cost == 6 && is_tour(&tour, 6));
+        assert!(held_karp(&generators::complete(EXACT_LIMIT + 1)).is_err());
+    }
+
+    #[test]
+    fn directed_tours() {
+        let mut graph = Graph::new();
+        for _ in 0..4 {
+            graph.add_node(());
+        }
+        for (a, b, w) in [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 3, 1),
+            (3, 0, 1),
+            (0, 2, 1),
+            (2, 1, 1),
+            (1, 3, 1),
+        ]
+        .iter()
+        {
+            graph.add_edge(*a, *b, *w);
+        }
+        // Only 0 1 2 3 and 0 2 1 3 are tours, both of cost 4
+        assert_eq!(held_karp(&graph).unwrap().unwrap().0, 4);
+        graph.add_edge(3, 1, 1);
+        graph.add_edge(1, 0, 9);
+        assert_eq!(held_karp(&graph).unwrap().unwrap().0, 4);
+        assert_eq!(two_opt(&graph, &[0, 2, 3, 1]).0, 4);
+    }
+}
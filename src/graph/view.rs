@@ -0,0 +1,322 @@
+use super::{Graph, GraphNode, NodeId};
+use crate::prelude::{EdgeRef, GraphBase, IntoEdges, IntoNeighbors, NodeCount, Visitable};
+
+/// A view of a graph that hides the nodes rejected by a predicate, along with
+/// every edge touching them. Node ids are the ones of the underlying graph, and
+/// `NodeCount::contains_node` tells algorithms which ones to skip.
+pub struct NodeFiltered<'a, G, F> {
+    graph: &'a G,
+    filter: F,
+}
+
+impl<'a, G, F: Fn(NodeId) -> bool> NodeFiltered<'a, G, F> {
+    pub fn new(graph: &'a G, filter: F) -> Self {
+        Self { graph, filter }
+    }
+
+    /// Returns `true` if `node` is part of the view
+    pub fn contains(&self, node: NodeId) -> bool {
+        (self.filter)(node)
+    }
+}
+
+impl<'a, G: GraphBase, F> GraphBase for NodeFiltered<'a, G, F> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+}
+
+impl<'a, G: NodeCount, F: Fn(NodeId) -> bool> NodeCount for NodeFiltered<'a, G, F> {
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn contains_node(&self, node: NodeId) -> bool {
+        self.graph.contains_node(node) && self.contains(node)
+    }
+}
+
+impl<'a, G: IntoNeighbors, F: Fn(NodeId) -> bool> IntoNeighbors for NodeFiltered<'a, G, F> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        if !self.contains(node) {
+            return Box::new(std::iter::empty());
+        }
+
+        Box::new(
+            self.graph
+                .neighbors(node)
+                .filter(move |n| self.contains(*n)),
+        )
+    }
+}
+
+impl<'a, G: IntoEdges, F: Fn(NodeId) -> bool> IntoEdges for NodeFiltered<'a, G, F> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        if !self.contains(node) {
+            return Box::new(std::iter::empty());
+        }
+
+        Box::new(
+            self.graph
+                .edges(node)
+                .filter(move |e| self.contains(e.target)),
+        )
+    }
+}
+
+impl<'a, G: Visitable, F> Visitable for NodeFiltered<'a, G, F> {
+    type Map = G::Map;
+
+    fn visit_map(&self) -> Self::Map {
+        self.graph.visit_map()
+    }
+}
+
+/// A view of a graph that only keeps the edges accepted by a predicate
+pub struct EdgeFiltered<'a, G, F> {
+    graph: &'a G,
+    filter: F,
+}
+
+impl<'a, G, F: Fn(&EdgeRef) -> bool> EdgeFiltered<'a, G, F> {
+    pub fn new(graph: &'a G, filter: F) -> Self {
+        Self { graph, filter }
+    }
+}
+
+impl<'a, G: GraphBase, F> GraphBase for EdgeFiltered<'a, G, F> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+}
+
+impl<'a, G: NodeCount, F> NodeCount for EdgeFiltered<'a, G, F> {
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn contains_node(&self, node: NodeId) -> bool {
+        self.graph.contains_node(node)
+    }
+}
+
+impl<'a, G: IntoEdges, F: Fn(&EdgeRef) -> bool> IntoNeighbors for EdgeFiltered<'a, G, F> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(self.edges(node).map(|e| e.target))
+    }
+}
+
+impl<'a, G: IntoEdges, F: Fn(&EdgeRef) -> bool> IntoEdges for EdgeFiltered<'a, G, F> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        Box::new(self.graph.edges(node).filter(move |e| (self.filter)(e)))
+    }
+}
+
+impl<'a, G: Visitable, F> Visitable for EdgeFiltered<'a, G, F> {
+    type Map = G::Map;
+
+    fn visit_map(&self) -> Self::Map {
+        self.graph.visit_map()
+    }
+}
+
+/// A view of a directed graph where every edge points the other way. Reversing an
+/// undirected graph has no effect.
+pub struct Reversed<'a, T> {
+    graph: &'a Graph<T>,
+}
+
+/// A view of a graph where every edge can be walked in both directions
+pub struct UndirectedView<'a, T> {
+    graph: &'a Graph<T>,
+}
+
+impl<T> Graph<T> {
+    /// Returns a view of this graph without the nodes rejected by `filter`
+    pub fn filter_nodes<'a, F>(
+        &'a self,
+        filter: F,
+    ) -> NodeFiltered<'a, Self, impl Fn(NodeId) -> bool + 'a>
+    where
+        F: Fn(&GraphNode<T>) -> bool + 'a,
+    {
+        NodeFiltered::new(self, move |id| filter(&self.nodes[id]))
+    }
+
+    /// Returns a view of this graph without the edges rejected by `filter`
+    pub fn filter_edges<F: Fn(&EdgeRef) -> bool>(&self, filter: F) -> EdgeFiltered<'_, Self, F> {
+        EdgeFiltered::new(self, filter)
+    }
+
+    /// Returns a view of this graph with its edges reversed
+    pub fn reversed(&self) -> Reversed<'_, T> {
+        Reversed { graph: self }
+    }
+
+    /// Returns a view of this graph that ignores edge directions
+    pub fn undirected_view(&self) -> UndirectedView<'_, T> {
+        UndirectedView { graph: self }
+    }
+
+    fn incoming_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeRef> + '_ {
+        self.nodes[node].incoming.iter().map(move |&id| {
+            let edge = &self.edges[id];
+            EdgeRef {
+                id,
+                source: node,
+                target: edge.from,
+                weight: edge.weight,
+            }
+        })
+    }
+}
+
+impl<'a, T> GraphBase for Reversed<'a, T> {
+    fn is_directed(&self) -> bool {
+        self.graph.is_directed()
+    }
+}
+
+impl<'a, T> NodeCount for Reversed<'a, T> {
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+impl<'a, T> IntoNeighbors for Reversed<'a, T> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(self.edges(node).map(|e| e.target))
+    }
+}
+
+impl<'a, T> IntoEdges for Reversed<'a, T> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        if self.graph.is_directed() {
+            Box::new(self.graph.incoming_edges(node))
+        } else {
+            IntoEdges::edges(self.graph, node)
+        }
+    }
+}
+
+impl<'a, T> Visitable for Reversed<'a, T> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        self.graph.visit_map()
+    }
+}
+
+impl<'a, T> GraphBase for UndirectedView<'a, T> {
+    fn is_directed(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, T> NodeCount for UndirectedView<'a, T> {
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+impl<'a, T> IntoNeighbors for UndirectedView<'a, T> {
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new(self.edges(node).map(|e| e.target))
+    }
+}
+
+impl<'a, T> IntoEdges for UndirectedView<'a, T> {
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_> {
+        let outgoing = IntoEdges::edges(self.graph, node);
+        if self.graph.is_directed() {
+            // Self loops are both outgoing and incoming, keep only one copy
+            let incoming = self
+                .graph
+                .incoming_edges(node)
+                .filter(move |e| e.target != node);
+            Box::new(outgoing.chain(incoming))
+        } else {
+            outgoing
+        }
+    }
+}
+
+impl<'a, T> Visitable for UndirectedView<'a, T> {
+    type Map = Vec<bool>;
+
+    fn visit_map(&self) -> Self::Map {
+        self.graph.visit_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algo::{connected_components, dijkstra, toposort, Bfs},
+        generators,
+    };
+
+    fn make_test_graph() -> Graph<bool> {
+        let mut graph = Graph::new();
+        let a = graph.add_node(true);
+        let b = graph.add_node(false);
+        let c = graph.add_node(true);
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, c, 1);
+        graph.add_edge(a, c, 5);
+
+        graph
+    }
+
+    #[test]
+    fn filter_nodes() {
+        let graph = make_test_graph();
+        let active = graph.filter_nodes(|node| **node);
+        assert_eq!(dijkstra(&active, 0), vec![Some(0), None, Some(5)]);
+        assert_eq!(Bfs::new(&active, 0).collect::<Vec<_>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn hidden_nodes_are_skipped() {
+        let graph = make_test_graph();
+        let active = graph.filter_nodes(|node| **node);
+        assert!(!active.contains_node(1));
+        assert_eq!(active.node_ids().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(toposort(&active), Ok(vec![0, 2]));
+
+        let path = generators::path(3);
+        let split = path.filter_nodes(|node| node.id() != 1);
+        assert_eq!(connected_components(&split), vec![0, usize::MAX, 1]);
+        let nested = NodeFiltered::new(&split, |node| node != 2);
+        assert_eq!(
+            connected_components(&nested),
+            vec![0, usize::MAX, usize::MAX]
+        );
+    }
+
+    #[test]
+    fn filter_edges() {
+        let graph = make_test_graph();
+        let light = graph.filter_edges(|edge| edge.weight < 5);
+        assert_eq!(dijkstra(&light, 0), vec![Some(0), Some(1), Some(2)]);
+        let heavy = graph.filter_edges(|edge| edge.weight >= 5);
+        assert_eq!(dijkstra(&heavy, 0), vec![Some(0), None, Some(5)]);
+    }
+
+    #[test]
+    fn reversed() {
+        let graph = make_test_graph();
+        let reversed = graph.reversed();
+        assert_eq!(dijkstra(&reversed, 2), vec![Some(2), Some(1), Some(0)]);
+        assert_eq!(toposort(&reversed), Ok(vec![2, 1, 0]));
+    }
+
+    #[test]
+    fn undirected_view() {
+        let graph = make_test_graph();
+        let view = graph.undirected_view();
+        assert!(!view.is_directed());
+        assert_eq!(Bfs::new(&view, 2).collect::<Vec<_>>(), vec![2, 1, 0]);
+    }
+}
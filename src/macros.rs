@@ -17,6 +17,74 @@ macro_rules! list {
     }
 }
 
+/// Builds a `Graph` from a list of edges between named nodes, returning the graph
+/// and a map from node names to ids. Nodes are created in order of first
+/// appearance, and edges weigh 1 unless a weight follows a colon.
+///
+/// `a -> b` adds an edge from `a` to `b`, while `a -- b` joins them both ways.
+/// Starting the list with `undirected;` builds an undirected graph, which only
+/// accepts `--`. A name on its own adds a node without edges.
+///
+/// ## Example
+/// ```
+/// # use fluffy_structs::graph;
+/// let (graph, names) = graph! { a -> b: 3, b -> c, c -- d, e };
+/// assert_eq!(graph.node_count(), 5);
+/// assert_eq!(graph.edge_count(), 4);
+/// let ab = graph.find_edge(names["a"], names["b"]).unwrap();
+/// assert_eq!(graph.edge(ab).unwrap().weight(), 3);
+///
+/// let (graph, _) = graph! { undirected; a -- b: 2, b -- c };
+/// assert!(!graph.is_directed());
+/// ```
+#[macro_export]
+macro_rules! graph {
+    (undirected; $( $edges:tt )*) => {
+        $crate::graph!(@build undirected, $crate::Graph::new_undirected(); $( $edges )*)
+    };
+    (@build $kind:ident, $new:expr; $( $edges:tt )*) => {
+        {
+            let mut graph = $new;
+            let mut names = ::std::collections::HashMap::<&'static str, $crate::NodeId>::new();
+            $crate::graph!(@edges $kind graph names; $( $edges )*);
+            (graph, names)
+        }
+    };
+    (@edges $kind:ident $graph:ident $names:ident;) => {};
+    (@edges directed $graph:ident $names:ident;
+        $from:ident -> $to:ident $( : $weight:expr )? $( , $( $rest:tt )* )?) => {
+        let from = $crate::graph!(@node $graph $names $from);
+        let to = $crate::graph!(@node $graph $names $to);
+        $graph.add_edge(from, to, $crate::graph!(@weight $( $weight )?));
+        $crate::graph!(@edges directed $graph $names; $( $( $rest )* )?);
+    };
+    (@edges $kind:ident $graph:ident $names:ident;
+        $from:ident -- $to:ident $( : $weight:expr )? $( , $( $rest:tt )* )?) => {
+        let from = $crate::graph!(@node $graph $names $from);
+        let to = $crate::graph!(@node $graph $names $to);
+        let weight = $crate::graph!(@weight $( $weight )?);
+        $graph.add_edge(from, to, weight);
+        if $graph.is_directed() {
+            $graph.add_edge(to, from, weight);
+        }
+        $crate::graph!(@edges $kind $graph $names; $( $( $rest )* )?);
+    };
+    (@edges $kind:ident $graph:ident $names:ident; $node:ident $( , $( $rest:tt )* )?) => {
+        let _ = $crate::graph!(@node $graph $names $node);
+        $crate::graph!(@edges $kind $graph $names; $( $( $rest )* )?);
+    };
+    (@node $graph:ident $names:ident $node:ident) => {
+        *$names
+            .entry(stringify!($node))
+            .or_insert_with(|| $graph.add_node(()))
+    };
+    (@weight) => { 1 };
+    (@weight $weight:expr) => { $weight };
+    ($( $edges:tt )*) => {
+        $crate::graph!(@build directed, $crate::Graph::new(); $( $edges )*)
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use crate::linked_list::LinkedList;
@@ -25,4 +93,19 @@ mod tests {
         let list = list! (1, 2, 3);
         assert_eq!(list.len(), 3);
     }
+
+    #[test]
+    fn graph_macro() {
+        let (graph, names) = graph! { a -> b: 3, b -> c, c -- a, d };
+        assert!(graph.is_directed());
+        assert_eq!((graph.node_count(), graph.edge_count()), (4, 4));
+        assert_eq!(names["d"], 3);
+        assert!(graph.find_edge(names["a"], names["c"]).is_some());
+        assert!(graph.find_edge(names["b"], names["a"]).is_none());
+
+        let (graph, names) = graph! { undirected; x -- y: 5, y -- z, };
+        assert_eq!(graph.edge_count(), 2);
+        let yx = graph.find_edge(names["y"], names["x"]).unwrap();
+        assert_eq!(graph.edge(yx).unwrap().weight(), 5);
+    }
 }
@@ -23,3 +23,85 @@ pub trait Traversable<T> {
 pub trait Sortable {
     fn sort(self, order: Order) -> Self;
 }
+
+pub use crate::graph::{EdgeId, NodeId};
+
+/// Base trait of every graph representation. Nodes of a graph are always identified
+/// by the indices in `0..node_count()`, so that algorithms can keep their per-node
+/// state in plain vectors.
+pub trait GraphBase {
+    fn is_directed(&self) -> bool;
+}
+
+/// A graph that knows how many nodes it holds
+pub trait NodeCount: GraphBase {
+    /// The number of node ids, hidden nodes included
+    fn node_count(&self) -> usize;
+
+    /// Returns `false` if `node` is hidden, like the nodes a filtered view
+    /// rejects. Hidden nodes keep their id but have no edges. Algorithms that
+    /// leave them out say so, the others see them as isolated nodes.
+    fn contains_node(&self, node: NodeId) -> bool {
+        node < self.node_count()
+    }
+
+    /// The nodes that are not hidden, in increasing order
+    fn node_ids(&self) -> Box<dyn Iterator<Item = NodeId> + '_> {
+        Box::new((0..self.node_count()).filter(move |node| self.contains_node(*node)))
+    }
+}
+
+/// A graph that can enumerate the neighbors of a node
+pub trait IntoNeighbors: GraphBase {
+    /// The nodes reachable from `node` through a single edge
+    fn neighbors(&self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + '_>;
+}
+
+/// A reference to an edge, as seen from one of its endpoints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeRef {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: usize,
+}
+
+/// A graph that can enumerate the edges leaving a node
+pub trait IntoEdges: IntoNeighbors {
+    /// The edges leaving `node`. On undirected graphs `source` is always `node`
+    /// and `target` the other endpoint.
+    fn edges(&self, node: NodeId) -> Box<dyn Iterator<Item = EdgeRef> + '_>;
+}
+
+/// Keeps track of the nodes visited by a traversal
+pub trait VisitMap {
+    /// Marks `node` as visited, returning `true` if it was not visited before
+    fn visit(&mut self, node: NodeId) -> bool;
+    fn is_visited(&self, node: NodeId) -> bool;
+}
+
+impl VisitMap for Vec<bool> {
+    fn visit(&mut self, node: NodeId) -> bool {
+        !std::mem::replace(&mut self[node], true)
+    }
+
+    fn is_visited(&self, node: NodeId) -> bool {
+        self[node]
+    }
+}
+
+impl VisitMap for std::collections::HashSet<NodeId> {
+    fn visit(&mut self, node: NodeId) -> bool {
+        self.insert(node)
+    }
+
+    fn is_visited(&self, node: NodeId) -> bool {
+        self.contains(&node)
+    }
+}
+
+/// A graph that can create a `VisitMap` sized for its nodes
+pub trait Visitable: GraphBase {
+    type Map: VisitMap;
+    fn visit_map(&self) -> Self::Map;
+}